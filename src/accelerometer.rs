@@ -1,8 +1,23 @@
 //! Accelerometer interface implementation.
 
-use crate::{Error, I2c, Mpu6886};
+use crate::model::Feature;
+use crate::registers::{accel_config, accel_config2, accel_offset_l};
+use crate::{registers, xyz_from_be_bytes, Error, Interface, Mpu6886};
+
+pub(crate) const GRAVITY: f32 = 9.80665;
+
+/// Accelerometer sensitivity at ±2 g, in LSB per g.
+pub const ACCEL_SENSITIVITY_2G: f32 = 16384.0;
+/// Accelerometer sensitivity at ±4 g, in LSB per g.
+pub const ACCEL_SENSITIVITY_4G: f32 = 8192.0;
+/// Accelerometer sensitivity at ±8 g, in LSB per g.
+pub const ACCEL_SENSITIVITY_8G: f32 = 4096.0;
+/// Accelerometer sensitivity at ±16 g, in LSB per g.
+pub const ACCEL_SENSITIVITY_16G: f32 = 2048.0;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccelScaleRange {
     Range2g,
     Range4g,
@@ -10,71 +25,410 @@ pub enum AccelScaleRange {
     Range16g,
 }
 
-// #[repr(u8)]
-// #[derive(Debug, Clone, Copy, PartialEq)]
-// pub enum AccelRate {
+impl AccelScaleRange {
+    /// Decodes the range from an ACCEL_CONFIG register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        let selection = (raw_value & accel_config::ACCEL_FS_SEL) >> 3;
+        match selection {
+            0 => AccelScaleRange::Range2g,
+            1 => AccelScaleRange::Range4g,
+            2 => AccelScaleRange::Range8g,
+            _ => AccelScaleRange::Range16g,
+        }
+    }
+
+    /// Encodes the range into an ACCEL_CONFIG register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        original_value & !accel_config::ACCEL_FS_SEL | (self as u8) << 3
+    }
+
+    /// Returns the sensitivity, in LSB per g.
+    pub fn lsb_per_g(self) -> f32 {
+        match self {
+            AccelScaleRange::Range2g => ACCEL_SENSITIVITY_2G,
+            AccelScaleRange::Range4g => ACCEL_SENSITIVITY_4G,
+            AccelScaleRange::Range8g => ACCEL_SENSITIVITY_8G,
+            AccelScaleRange::Range16g => ACCEL_SENSITIVITY_16G,
+        }
+    }
 
-// }
+    /// Returns the sensitivity in LSB per g as an integer, for integer-only
+    /// conversions.
+    pub const fn lsb_per_g_i32(self) -> i32 {
+        16384 >> (self as i32)
+    }
 
-impl<I2C: I2c> Mpu6886<I2C> {
-    pub fn get_accel_scale_range(&mut self) -> Result<AccelScaleRange, Error> {
-        let raw_value = self.read_u8(0x1C)?;
-        let selection = (raw_value & 0b00011000) >> 3;
-        match selection {
+    /// Returns the full scale value, in g.
+    pub fn max_value(self) -> f32 {
+        match self {
+            AccelScaleRange::Range2g => 2.0,
+            AccelScaleRange::Range4g => 4.0,
+            AccelScaleRange::Range8g => 8.0,
+            AccelScaleRange::Range16g => 16.0,
+        }
+    }
+}
+
+impl core::fmt::Display for AccelScaleRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "±{} g", self.max_value())
+    }
+}
+
+/// Converts from the ACCEL_FS_SEL field value, returning the value back if it
+/// is out of range.
+impl TryFrom<u8> for AccelScaleRange {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
             0 => Ok(AccelScaleRange::Range2g),
             1 => Ok(AccelScaleRange::Range4g),
             2 => Ok(AccelScaleRange::Range8g),
-            _ => Ok(AccelScaleRange::Range16g),
+            3 => Ok(AccelScaleRange::Range16g),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Converts into the ACCEL_FS_SEL field value.
+impl From<AccelScaleRange> for u8 {
+    fn from(value: AccelScaleRange) -> Self {
+        value as u8
+    }
+}
+
+/// Converts raw accelerometer readings with the given range.
+pub(crate) fn scale_acceleration(raw: (i16, i16, i16), range: AccelScaleRange) -> (f32, f32, f32) {
+    let factor = range.lsb_per_g();
+    (
+        raw.0 as f32 / factor * GRAVITY,
+        raw.1 as f32 / factor * GRAVITY,
+        raw.2 as f32 / factor * GRAVITY,
+    )
+}
+
+/// Zeroes the axes of an acceleration, in m/s², closer to zero than
+/// `threshold_mg`.
+pub(crate) fn apply_dead_band(value: (f32, f32, f32), threshold_mg: u16) -> (f32, f32, f32) {
+    let threshold = threshold_mg as f32 / 1000.0 * GRAVITY;
+    let filter = |value: f32| if value.abs() < threshold { 0.0 } else { value };
+    (filter(value.0), filter(value.1), filter(value.2))
+}
+
+/// Accelerometer DLPF bandwidth, set through A_DLPF_CFG and ACCEL_FCHOICE_B.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccelBandwidth {
+    /// 218.1 Hz, 1 kHz internal rate.
+    Hz218,
+    /// 99 Hz, 1 kHz internal rate.
+    Hz99,
+    /// 44.8 Hz, 1 kHz internal rate.
+    Hz45,
+    /// 21.2 Hz, 1 kHz internal rate.
+    Hz21,
+    /// 10.2 Hz, 1 kHz internal rate.
+    Hz10,
+    /// 5.1 Hz, 1 kHz internal rate.
+    Hz5,
+    /// 420 Hz, 1 kHz internal rate.
+    Hz420,
+    /// 1046 Hz with the DLPF bypassed, 4 kHz internal rate.
+    Hz1046,
+}
+
+impl AccelBandwidth {
+    /// Decodes the bandwidth from an ACCEL_CONFIG2 register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        if raw_value & accel_config2::ACCEL_FCHOICE_B != 0 {
+            return AccelBandwidth::Hz1046;
+        }
+        match raw_value & accel_config2::A_DLPF_CFG {
+            0 | 1 => AccelBandwidth::Hz218,
+            2 => AccelBandwidth::Hz99,
+            3 => AccelBandwidth::Hz45,
+            4 => AccelBandwidth::Hz21,
+            5 => AccelBandwidth::Hz10,
+            6 => AccelBandwidth::Hz5,
+            _ => AccelBandwidth::Hz420,
+        }
+    }
+
+    /// Encodes the bandwidth into an ACCEL_CONFIG2 register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        let fields = match self {
+            AccelBandwidth::Hz218 => 1,
+            AccelBandwidth::Hz99 => 2,
+            AccelBandwidth::Hz45 => 3,
+            AccelBandwidth::Hz21 => 4,
+            AccelBandwidth::Hz10 => 5,
+            AccelBandwidth::Hz5 => 6,
+            AccelBandwidth::Hz420 => 7,
+            AccelBandwidth::Hz1046 => accel_config2::ACCEL_FCHOICE_B,
+        };
+        original_value & !(accel_config2::ACCEL_FCHOICE_B | accel_config2::A_DLPF_CFG) | fields
+    }
+}
+
+/// Accelerometer offsets, removed by the chip from every reading.
+///
+/// The registers hold 15 bit values with 0.98 mg per LSB (twice the ±16 g
+/// resolution), independent of the scale range. Values are clamped to
+/// -16384..=16383.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccelOffsets {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// Averaging filter used in accelerometer low power mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccelAveraging {
+    Avg4,
+    Avg8,
+    Avg16,
+    Avg32,
+}
+
+impl AccelAveraging {
+    /// Decodes the averaging from an ACCEL_CONFIG2 register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        let selection = (raw_value & accel_config2::DEC2_CFG) >> 4;
+        match selection {
+            0 => AccelAveraging::Avg4,
+            1 => AccelAveraging::Avg8,
+            2 => AccelAveraging::Avg16,
+            _ => AccelAveraging::Avg32,
         }
     }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Returns the scale range cached by the driver, without bus traffic.
+    pub fn accel_scale_range(&self) -> AccelScaleRange {
+        self.acc_range
+    }
+
+    pub fn get_accel_scale_range(&mut self) -> Result<AccelScaleRange, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::ACCEL_CONFIG)?;
+        Ok(AccelScaleRange::from_reg(raw_value))
+    }
 
-    pub fn set_accel_scale_range(&mut self, value: AccelScaleRange) -> Result<(), Error> {
-        let original_value = self.read_u8(0x1C)?;
-        let choice_value = value as u8;
-        let reg_value = (original_value & 0b11100111) | choice_value << 3;
-        self.write_u8(0x1C, reg_value)?;
+    pub fn set_accel_scale_range(
+        &mut self,
+        value: AccelScaleRange,
+    ) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::ACCEL_CONFIG)?;
+        let reg_value = value.apply_to_reg(original_value);
+        self.write_u8(registers::ACCEL_CONFIG, reg_value)?;
         self.acc_range = value;
         Ok(())
     }
 
-    pub fn turn_off_accelerometer(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6C)?;
-        let new_value = original_value | 0b00111000;
-        self.write_u8(0x6C, new_value)
+    /// Sets the accelerometer DLPF bandwidth, [`AccelBandwidth::Hz1046`]
+    /// bypasses the DLPF through ACCEL_FCHOICE_B, e.g. to capture shocks.
+    pub fn set_accel_bandwidth(
+        &mut self,
+        bandwidth: AccelBandwidth,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::AccelBandwidth)?;
+        let original_value = self.read_u8(registers::ACCEL_CONFIG2)?;
+        let new_value = bandwidth.apply_to_reg(original_value);
+        self.write_u8(registers::ACCEL_CONFIG2, new_value)
+    }
+
+    pub fn get_accel_bandwidth(&mut self) -> Result<AccelBandwidth, Error<DI::Error>> {
+        self.model.require(Feature::AccelBandwidth)?;
+        let raw_value = self.read_u8(registers::ACCEL_CONFIG2)?;
+        Ok(AccelBandwidth::from_reg(raw_value))
+    }
+
+    /// Sets the number of samples averaged in accelerometer low power mode.
+    pub fn set_accel_averaging(
+        &mut self,
+        averaging: AccelAveraging,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::AccelAveraging)?;
+        let original_value = self.read_u8(registers::ACCEL_CONFIG2)?;
+        let new_value = original_value & !accel_config2::DEC2_CFG | (averaging as u8) << 4;
+        self.write_u8(registers::ACCEL_CONFIG2, new_value)
+    }
+
+    pub fn get_accel_averaging(&mut self) -> Result<AccelAveraging, Error<DI::Error>> {
+        self.model.require(Feature::AccelAveraging)?;
+        let raw_value = self.read_u8(registers::ACCEL_CONFIG2)?;
+        Ok(AccelAveraging::from_reg(raw_value))
+    }
+
+    /// Writes the XA/YA/ZA_OFFSET registers, keeping the reserved bits.
+    pub fn set_accel_offsets(&mut self, offsets: AccelOffsets) -> Result<(), Error<DI::Error>> {
+        for (reg, value) in self
+            .model
+            .accel_offset_regs()
+            .into_iter()
+            .zip([offsets.x, offsets.y, offsets.z])
+        {
+            let reserved = self.read_u8(reg + 1)? & !accel_offset_l::OFFSET_L;
+            let value = value.clamp(-16384, 16383) << 1;
+            let [high, low] = value.to_be_bytes();
+            self.write_u8(reg, high)?;
+            self.write_u8(reg + 1, low | reserved)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the XA/YA/ZA_OFFSET registers.
+    pub fn get_accel_offsets(&mut self) -> Result<AccelOffsets, Error<DI::Error>> {
+        let mut values: [i16; 3] = [0; 3];
+        let regs = self.model.accel_offset_regs();
+        for (reg, value) in regs.into_iter().zip(values.iter_mut()) {
+            *value = self.read_u16(reg)? as i16 >> 1;
+        }
+        let [x, y, z] = values;
+        Ok(AccelOffsets { x, y, z })
+    }
+
+    /// Sets a software dead-band, in mg: axes of the converted acceleration
+    /// closer to zero than `threshold_mg` read 0. 0 disables it.
+    ///
+    /// It's applied after the calibration and mounting correction.
+    pub fn set_accel_dead_band(&mut self, threshold_mg: u16) {
+        self.accel_dead_band = threshold_mg;
+    }
+
+    /// Returns the software dead-band, in mg.
+    pub fn accel_dead_band(&self) -> u16 {
+        self.accel_dead_band
+    }
+
+    /// Converts raw accelerometer readings, e.g. of a
+    /// [`FifoSample`](crate::FifoSample), to m/s² with the current range,
+    /// calibration, mounting correction and dead-band.
+    pub fn scale_acceleration(&self, raw: (i16, i16, i16)) -> (f32, f32, f32) {
+        self.conversion().acceleration(raw)
     }
 
-    pub fn turn_on_accelerometer(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6C)?;
-        let new_value = original_value & 0b11000111;
-        self.write_u8(0x6C, new_value)
+    /// Returns measured acceleration, (X, Y, Z), in m/s².
+    pub fn acceleration(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        let raw = self.acceleration_raw()?;
+        Ok(self.scale_acceleration(raw))
+    }
+
+    /// Same as [`Mpu6886::acceleration`], in m/s².
+    pub fn acceleration_ms2(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        self.acceleration()
     }
 
     /// Returns measured acceleration, (X, Y, Z), in g.
-    pub fn acceleration(&mut self) -> Result<(f32, f32, f32), Error> {
+    pub fn acceleration_g(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        let (x, y, z) = self.acceleration()?;
+        Ok((x / GRAVITY, y / GRAVITY, z / GRAVITY))
+    }
+
+    /// Returns measured acceleration, (X, Y, Z), in mg, using integer math
+    /// only.
+    ///
+    /// For targets without FPU. The software calibration isn't applied, the
+    /// dead-band is.
+    pub fn acceleration_mg(&mut self) -> Result<(i32, i32, i32), Error<DI::Error>> {
+        let (x, y, z) = self.acceleration_raw()?;
+        let lsb_per_g = self.acc_range.lsb_per_g_i32();
+        let mg = |raw: i16| raw as i32 * 1000 / lsb_per_g;
+        let (x, y, z) = self.mounting.apply_fixed((mg(x), mg(y), mg(z)));
+        // after the mounting correction, like the f32 readings
+        let threshold = self.accel_dead_band as i32;
+        let filter = |value: i32| if value.abs() < threshold { 0 } else { value };
+        Ok((filter(x), filter(y), filter(z)))
+    }
+
+    /// Returns raw accelerometer readings, (X, Y, Z), in LSB of the current
+    /// scale range.
+    pub fn acceleration_raw(&mut self) -> Result<(i16, i16, i16), Error<DI::Error>> {
+        self.check_ready()?;
         let mut xyz_buf: [u8; 6] = [0; 6];
-        self.read_buf(0x3B, &mut xyz_buf)?;
-        let x_raw = (xyz_buf[0] as u16) << 8 | (xyz_buf[1] as u16);
-        let y_raw = (xyz_buf[2] as u16) << 8 | (xyz_buf[3] as u16);
-        let z_raw = (xyz_buf[4] as u16) << 8 | (xyz_buf[5] as u16);
-        const GRAVITY: f32 = 9.80665;
-        let factor: f32 = match self.acc_range {
-            AccelScaleRange::Range2g => 16384.0,
-            AccelScaleRange::Range4g => 8192.0,
-            AccelScaleRange::Range8g => 4096.0,
-            AccelScaleRange::Range16g => 2048.0,
+        self.read_buf(registers::ACCEL_XOUT_H, &mut xyz_buf)?;
+        Ok(xyz_from_be_bytes(&xyz_buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::MPU6886_DEFAULT_ADDR;
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+
+    fn read(reg: u8, value: &[u8]) -> Transaction {
+        Transaction::write_read(MPU6886_DEFAULT_ADDR, vec![reg], value.to_vec())
+    }
+
+    fn write(reg: u8, value: u8) -> Transaction {
+        Transaction::write(MPU6886_DEFAULT_ADDR, vec![reg, value])
+    }
+
+    #[test]
+    fn integer_sensitivity_matches_constants() {
+        for range in [
+            AccelScaleRange::Range2g,
+            AccelScaleRange::Range4g,
+            AccelScaleRange::Range8g,
+            AccelScaleRange::Range16g,
+        ] {
+            assert_eq!(range.lsb_per_g_i32() as f32, range.lsb_per_g());
+        }
+    }
+
+    #[test]
+    fn offsets_keep_reserved_bit() {
+        let expectations = [
+            read(registers::XA_OFFSET_L, &[0b00000001]),
+            write(registers::XA_OFFSET_H, 0xFF),
+            write(registers::XA_OFFSET_L, 0xFF),
+            read(registers::YA_OFFSET_L, &[0b11111110]),
+            write(registers::YA_OFFSET_H, 0x7F),
+            write(registers::YA_OFFSET_L, 0xFE),
+            read(registers::ZA_OFFSET_L, &[0b00000001]),
+            write(registers::ZA_OFFSET_H, 0x80),
+            write(registers::ZA_OFFSET_L, 0x01),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        // y and z are clamped to the 15 bit range
+        let offsets = AccelOffsets {
+            x: -1,
+            y: 20000,
+            z: -20000,
         };
-        let x_real = x_raw as i16 as f32 / factor * GRAVITY;
-        let y_real = y_raw as i16 as f32 / factor * GRAVITY;
-        let z_real = z_raw as i16 as f32 / factor * GRAVITY;
-        Ok((x_real, y_real, z_real))
+        sensor.set_accel_offsets(offsets).unwrap();
+        sensor.destroy().done();
     }
 
-    pub fn acceleration_raw(&mut self) -> Result<(i16, i16, i16), Error> {
-        let mut xyz_buf: [u8; 6] = [0; 6];
-        self.read_buf(0x3B, &mut xyz_buf)?;
-        let x_raw = (xyz_buf[0] as u16) << 8 | (xyz_buf[1] as u16);
-        let y_raw = (xyz_buf[2] as u16) << 8 | (xyz_buf[3] as u16);
-        let z_raw = (xyz_buf[4] as u16) << 8 | (xyz_buf[5] as u16);
-        Ok((x_raw as i16, y_raw as i16, z_raw as i16))
+    #[test]
+    fn offsets_drop_reserved_bit() {
+        let expectations = [
+            read(registers::XA_OFFSET_H, &[0xFF, 0xFF]),
+            read(registers::YA_OFFSET_H, &[0x00, 0x03]),
+            read(registers::ZA_OFFSET_H, &[0x80, 0x01]),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        let offsets = sensor.get_accel_offsets().unwrap();
+        assert_eq!(
+            offsets,
+            AccelOffsets {
+                x: -1,
+                y: 1,
+                z: -16384,
+            }
+        );
+        sensor.destroy().done();
     }
 }
@@ -0,0 +1,39 @@
+//! `accelerometer` crate trait implementations.
+
+use crate::{Error, Interface, Mpu6886};
+
+use core::fmt::Debug;
+
+use ::accelerometer::vector::{F32x3, I16x3};
+use ::accelerometer::{Accelerometer, ErrorKind, RawAccelerometer};
+
+fn accelerometer_error<E: Debug>(error: Error<E>) -> ::accelerometer::Error<Error<E>> {
+    let kind = match error {
+        Error::Transfer { .. } => ErrorKind::Bus,
+        _ => ErrorKind::Device,
+    };
+    ::accelerometer::Error::new_with_cause(kind, error)
+}
+
+impl<DI: Interface> RawAccelerometer<I16x3> for Mpu6886<DI> {
+    type Error = Error<DI::Error>;
+
+    fn accel_raw(&mut self) -> Result<I16x3, ::accelerometer::Error<Self::Error>> {
+        let (x, y, z) = self.acceleration_raw().map_err(accelerometer_error)?;
+        Ok(I16x3::new(x, y, z))
+    }
+}
+
+impl<DI: Interface> Accelerometer for Mpu6886<DI> {
+    type Error = Error<DI::Error>;
+
+    /// Returns the calibrated acceleration in g.
+    fn accel_norm(&mut self) -> Result<F32x3, ::accelerometer::Error<Self::Error>> {
+        let (x, y, z) = self.acceleration_g().map_err(accelerometer_error)?;
+        Ok(F32x3::new(x, y, z))
+    }
+
+    fn sample_rate(&mut self) -> Result<f32, ::accelerometer::Error<Self::Error>> {
+        self.output_data_rate().map_err(accelerometer_error)
+    }
+}
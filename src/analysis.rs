@@ -0,0 +1,134 @@
+//! Noise analysis of a stationary device.
+//!
+//! Collect gyroscope readings with [`Mpu6886::collect_gyro`], then compute
+//! the Allan deviation for a range of cluster sizes. Its minimum is the bias
+//! instability, the value at 1 s the angle random walk:
+//!
+//! ```rust,ignore
+//! let mut samples = [(0.0, 0.0, 0.0); 4096];
+//! sensor.collect_gyro(&mut delay, 10_000, &mut samples)?;
+//! for cluster_size in [1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024] {
+//!     let tau = cluster_size as f32 * 0.01;
+//!     let deviation = mpu6886::analysis::allan_deviation(&samples, cluster_size);
+//! }
+//! ```
+//!
+//! [`Mpu6886::measure_noise_density`] gives figures comparable to the
+//! datasheet's noise specification.
+
+use core::f32::consts::PI;
+
+use crate::accelerometer::GRAVITY;
+use crate::{Error, Interface, Mpu6886, SampleStats};
+
+use embedded_hal::delay::DelayNs;
+
+/// Overlapping Allan variance of one channel.
+fn channel_allan_variance(
+    samples: &[(f32, f32, f32)],
+    cluster_size: usize,
+    channel: impl Fn(&(f32, f32, f32)) -> f32,
+) -> f32 {
+    let m = cluster_size;
+    let value = |i: usize| channel(&samples[i]) as f64;
+    // sums of two adjacent clusters, slid over the samples
+    let mut first: f64 = (0..m).map(value).sum();
+    let mut second: f64 = (m..2 * m).map(value).sum();
+    let mut acc = (second - first) * (second - first);
+    for i in 1..=samples.len() - 2 * m {
+        first += value(i + m - 1) - value(i - 1);
+        second += value(i + 2 * m - 1) - value(i + m - 1);
+        acc += (second - first) * (second - first);
+    }
+    let count = (samples.len() - 2 * m + 1) as f64;
+    (acc / (2.0 * (m * m) as f64 * count)) as f32
+}
+
+/// Returns the overlapping Allan variance, (X, Y, Z), for clusters of
+/// `cluster_size` samples, in the squared unit of `samples`.
+///
+/// Returns `None` unless `samples` holds at least two clusters.
+pub fn allan_variance(samples: &[(f32, f32, f32)], cluster_size: usize) -> Option<(f32, f32, f32)> {
+    if cluster_size == 0 || samples.len() < 2 * cluster_size {
+        return None;
+    }
+    Some((
+        channel_allan_variance(samples, cluster_size, |s| s.0),
+        channel_allan_variance(samples, cluster_size, |s| s.1),
+        channel_allan_variance(samples, cluster_size, |s| s.2),
+    ))
+}
+
+/// Returns the Allan deviation, (X, Y, Z), for clusters of `cluster_size`
+/// samples, in the unit of `samples`.
+pub fn allan_deviation(
+    samples: &[(f32, f32, f32)],
+    cluster_size: usize,
+) -> Option<(f32, f32, f32)> {
+    let (x, y, z) = allan_variance(samples, cluster_size)?;
+    Some((libm::sqrtf(x), libm::sqrtf(y), libm::sqrtf(z)))
+}
+
+/// Noise density per axis, see [`Mpu6886::measure_noise_density`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseDensity {
+    /// Accelerometer noise, (X, Y, Z), in µg/√Hz.
+    pub accel: (f32, f32, f32),
+    /// Gyroscope noise, (X, Y, Z), in mdps/√Hz.
+    pub gyro: (f32, f32, f32),
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Takes `samples` readings at the configured output data rate and
+    /// returns the noise density.
+    ///
+    /// The device must be held still. The noise is assumed white up to half
+    /// the output data rate, so the DLPF bandwidth should be at least that.
+    pub fn measure_noise_density(
+        &mut self,
+        delay: &mut impl DelayNs,
+        samples: u32,
+    ) -> Result<NoiseDensity, Error<DI::Error>> {
+        let odr = self.output_data_rate()?;
+        let period_us = (1_000_000.0 / odr) as u32;
+        let mut stats = SampleStats::default();
+        for _ in 0..samples {
+            stats.add(&self.read_all()?);
+            delay.delay_us(period_us);
+        }
+        let sqrt_bandwidth = libm::sqrtf(odr / 2.0);
+        let accel = |variance: f32| libm::sqrtf(variance) / sqrt_bandwidth / GRAVITY * 1e6;
+        let gyro = |variance: f32| libm::sqrtf(variance) / sqrt_bandwidth * 180.0 / PI * 1e3;
+        Ok(NoiseDensity {
+            accel: (
+                accel(stats.accel.0.variance()),
+                accel(stats.accel.1.variance()),
+                accel(stats.accel.2.variance()),
+            ),
+            gyro: (
+                gyro(stats.gyro.0.variance()),
+                gyro(stats.gyro.1.variance()),
+                gyro(stats.gyro.2.variance()),
+            ),
+        })
+    }
+
+    /// Fills `samples` with gyroscope readings in rad/s, `period_us` apart.
+    ///
+    /// The device must be held still. Set the output data rate to match the
+    /// period, the cluster time of the analysis is `cluster_size * period`.
+    pub fn collect_gyro(
+        &mut self,
+        delay: &mut impl DelayNs,
+        period_us: u32,
+        samples: &mut [(f32, f32, f32)],
+    ) -> Result<(), Error<DI::Error>> {
+        for sample in samples.iter_mut() {
+            *sample = self.gyro()?;
+            delay.delay_us(period_us);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,579 @@
+//! Async driver implementation.
+
+use crate::accelerometer::GRAVITY;
+use crate::measurement::Conversion;
+use crate::model::Feature;
+use crate::registers::{accel_intel_ctrl, int_enable, pwr_mgmt_1};
+use crate::{
+    check_ready, chunk_register, registers, xyz_from_be_bytes, AccelBandwidth, AccelScaleRange,
+    AxisMap, BusOperation, CalibrationData, ClockSource, Error, GyroScaleRange, InterruptLevel,
+    InterruptPinConfig, InterruptStatus, Measurement, Model, Mounting, PowerMode,
+    RawAccelGyroFrame, RawMeasurement, SensorAxes, MPU6886_DEFAULT_ADDR,
+};
+
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+#[cfg(feature = "stream")]
+use futures::Stream;
+
+/// Async variant of [`Mpu6886`](crate::Mpu6886).
+///
+/// Mirrors the blocking API, but every bus transaction is awaited.
+#[derive(Debug)]
+pub struct Mpu6886Async<I2C> {
+    i2c: I2C,
+    address: u8,
+    acc_range: AccelScaleRange,
+    gyro_range: GyroScaleRange,
+    calibration: CalibrationData,
+    model: Model,
+    mounting: Mounting,
+    accel_dead_band: u16,
+    initialized: bool,
+    asleep: bool,
+    max_read_len: Option<usize>,
+}
+
+impl<I2C: I2c> Mpu6886Async<I2C> {
+    #[must_use]
+    pub fn new(i2c: I2C) -> Self {
+        Self::new_with_addr(i2c, MPU6886_DEFAULT_ADDR)
+    }
+
+    #[must_use]
+    pub fn new_with_addr(i2c: I2C, address: u8) -> Self {
+        Self::new_with_model(i2c, address, Model::Mpu6886)
+    }
+
+    /// Creates a driver for another chip of the family at the given address.
+    #[must_use]
+    pub fn new_with_model(i2c: I2C, address: u8, model: Model) -> Self {
+        Self {
+            i2c,
+            address,
+            acc_range: AccelScaleRange::Range2g,
+            gyro_range: GyroScaleRange::Range250Dps,
+            calibration: CalibrationData::default(),
+            model,
+            mounting: Mounting::default(),
+            accel_dead_band: 0,
+            initialized: false,
+            asleep: true,
+            max_read_len: None,
+        }
+    }
+
+    /// Returns the chip model the driver was created for.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    pub fn destroy(self) -> I2C {
+        self.i2c
+    }
+
+    /// Sets the software calibration applied to converted readings.
+    pub fn set_calibration(&mut self, calibration: CalibrationData) {
+        self.calibration = calibration;
+    }
+
+    pub fn calibration(&self) -> CalibrationData {
+        self.calibration
+    }
+
+    /// See [`Mpu6886::set_accel_dead_band`](crate::Mpu6886::set_accel_dead_band).
+    pub fn set_accel_dead_band(&mut self, threshold_mg: u16) {
+        self.accel_dead_band = threshold_mg;
+    }
+
+    pub fn accel_dead_band(&self) -> u16 {
+        self.accel_dead_band
+    }
+
+    /// See [`Mpu6886::set_max_read_len`](crate::Mpu6886::set_max_read_len).
+    pub fn set_max_read_len(&mut self, max_len: Option<usize>) {
+        self.max_read_len = max_len.map(|len| len.max(1));
+    }
+
+    /// See [`Mpu6886::scale_acceleration`](crate::Mpu6886::scale_acceleration).
+    pub fn scale_acceleration(&self, raw: (i16, i16, i16)) -> (f32, f32, f32) {
+        self.conversion().acceleration(raw)
+    }
+
+    fn conversion(&self) -> Conversion<'_> {
+        Conversion::new(
+            self.acc_range,
+            self.gyro_range,
+            &self.calibration,
+            &self.mounting,
+            self.model,
+            self.accel_dead_band,
+        )
+    }
+
+    /// Sets how the chip is mounted, see [`Mpu6886::set_axis_mapping`](crate::Mpu6886::set_axis_mapping).
+    pub fn set_axis_mapping(&mut self, axis_map: AxisMap) {
+        self.mounting.axis_map = axis_map;
+    }
+
+    pub fn axis_mapping(&self) -> AxisMap {
+        self.mounting.axis_map
+    }
+
+    /// Sets the misalignment correction, see [`Mpu6886::set_alignment_matrix`](crate::Mpu6886::set_alignment_matrix).
+    pub fn set_alignment_matrix(&mut self, matrix: [[f32; 3]; 3]) {
+        self.mounting.set_alignment(matrix);
+    }
+
+    pub fn alignment_matrix(&self) -> [[f32; 3]; 3] {
+        self.mounting.alignment()
+    }
+
+    /// Reads the WHO_AM_I register.
+    pub async fn who_am_i(&mut self) -> Result<u8, Error<I2C::Error>> {
+        self.read_u8(registers::WHO_AM_I).await
+    }
+
+    /// Checks chip version and load current state.
+    pub async fn init(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.init_with_ids(&[self.model.who_am_i()]).await
+    }
+
+    /// Like [`Mpu6886Async::init`], but accepts any of the given WHO_AM_I
+    /// values, for clones reporting a different ID.
+    pub async fn init_with_ids(&mut self, accepted_ids: &[u8]) -> Result<(), Error<I2C::Error>> {
+        let chip_id = self.who_am_i().await?;
+        if !accepted_ids.contains(&chip_id) {
+            Err(Error::UnknownChip(chip_id))
+        } else {
+            self.init_unchecked().await
+        }
+    }
+
+    /// Loads current state without checking the chip version.
+    pub async fn init_unchecked(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.acc_range = self.get_accel_scale_range().await?;
+        self.gyro_range = self.get_gyro_scale_range().await?;
+        self.asleep = self.read_u8(registers::PWR_MGMT_1).await? & pwr_mgmt_1::SLEEP != 0;
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Resets the sensor to initial state.
+    ///
+    /// The chip comes back asleep, call [`Mpu6886Async::init`] again once
+    /// it's ready.
+    pub async fn reset(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.write_u8(registers::PWR_MGMT_1, pwr_mgmt_1::DEVICE_RESET)
+            .await?;
+        // also reset internal state
+        self.acc_range = AccelScaleRange::Range2g;
+        self.gyro_range = GyroScaleRange::Range250Dps;
+        self.asleep = true;
+        self.initialized = false;
+        Ok(())
+    }
+
+    #[deprecated(note = "use `set_power_mode(PowerMode::Sleep)`")]
+    pub async fn sleep(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value | pwr_mgmt_1::SLEEP;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    /// Wakes the inertial sensor up.
+    ///
+    /// See [`Mpu6886::wake`](crate::Mpu6886::wake).
+    #[deprecated(note = "use `init` and `set_power_mode(PowerMode::SixAxisLowNoise)`")]
+    pub async fn wake(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value & !pwr_mgmt_1::SLEEP;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await?;
+        // also load state from chip
+        self.acc_range = self.get_accel_scale_range().await?;
+        self.gyro_range = self.get_gyro_scale_range().await?;
+        self.initialized = true;
+        Ok(())
+    }
+
+    pub async fn set_clock_source(&mut self, source: ClockSource) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = source.apply_to_reg(original_value);
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    pub async fn get_clock_source(&mut self) -> Result<ClockSource, Error<I2C::Error>> {
+        let raw_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        Ok(ClockSource::from_reg(raw_value))
+    }
+
+    /// See [`Mpu6886::set_power_mode`](crate::Mpu6886::set_power_mode).
+    pub async fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), Error<I2C::Error>> {
+        if mode == PowerMode::Standby {
+            self.model.require(Feature::GyroStandby)?;
+        }
+        let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1).await?;
+        let pwr_mgmt_2 = self.read_u8(registers::PWR_MGMT_2).await?;
+        let (new_pwr_mgmt_1, new_pwr_mgmt_2) = mode.apply_to_regs(pwr_mgmt_1, pwr_mgmt_2);
+        self.write_u8(registers::PWR_MGMT_1, new_pwr_mgmt_1).await?;
+        match new_pwr_mgmt_2 {
+            Some(value) => self.write_u8(registers::PWR_MGMT_2, value).await,
+            None => Ok(()),
+        }
+    }
+
+    /// See [`Mpu6886::get_power_mode`](crate::Mpu6886::get_power_mode).
+    pub async fn get_power_mode(&mut self) -> Result<Option<PowerMode>, Error<I2C::Error>> {
+        let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1).await?;
+        let pwr_mgmt_2 = self.read_u8(registers::PWR_MGMT_2).await?;
+        Ok(PowerMode::from_regs(pwr_mgmt_1, pwr_mgmt_2))
+    }
+
+    /// Powers exactly the given axes, the others go to standby.
+    pub async fn set_enabled_axes(&mut self, axes: SensorAxes) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_2).await?;
+        let new_value = axes.apply_to_reg(original_value);
+        self.write_u8(registers::PWR_MGMT_2, new_value).await
+    }
+
+    pub async fn get_enabled_axes(&mut self) -> Result<SensorAxes, Error<I2C::Error>> {
+        let raw_value = self.read_u8(registers::PWR_MGMT_2).await?;
+        Ok(SensorAxes::from_reg(raw_value))
+    }
+
+    pub async fn disable_temperature_sensor(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value | pwr_mgmt_1::TEMP_DIS;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    pub async fn enable_temperature_sensor(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value & !pwr_mgmt_1::TEMP_DIS;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    pub async fn temperature(&mut self) -> Result<f32, Error<I2C::Error>> {
+        let raw_value = self.temperature_raw().await?;
+        Ok(self.model.scale_temperature(raw_value))
+    }
+
+    /// Returns the raw, signed temperature reading.
+    pub async fn temperature_raw(&mut self) -> Result<i16, Error<I2C::Error>> {
+        self.check_ready()?;
+        let mut buf: [u8; 2] = [0; 2];
+        self.read_buf(registers::TEMP_OUT_H, &mut buf).await?;
+        Ok(i16::from_be_bytes(buf))
+    }
+
+    /// Returns the scale range cached by the driver, without bus traffic.
+    pub fn accel_scale_range(&self) -> AccelScaleRange {
+        self.acc_range
+    }
+
+    pub async fn get_accel_scale_range(&mut self) -> Result<AccelScaleRange, Error<I2C::Error>> {
+        let raw_value = self.read_u8(registers::ACCEL_CONFIG).await?;
+        Ok(AccelScaleRange::from_reg(raw_value))
+    }
+
+    pub async fn set_accel_scale_range(
+        &mut self,
+        value: AccelScaleRange,
+    ) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::ACCEL_CONFIG).await?;
+        let reg_value = value.apply_to_reg(original_value);
+        self.write_u8(registers::ACCEL_CONFIG, reg_value).await?;
+        self.acc_range = value;
+        Ok(())
+    }
+
+    /// Sets the accelerometer DLPF bandwidth, [`AccelBandwidth::Hz1046`]
+    /// bypasses the DLPF through ACCEL_FCHOICE_B.
+    pub async fn set_accel_bandwidth(
+        &mut self,
+        bandwidth: AccelBandwidth,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.model.require(Feature::AccelBandwidth)?;
+        let original_value = self.read_u8(registers::ACCEL_CONFIG2).await?;
+        let new_value = bandwidth.apply_to_reg(original_value);
+        self.write_u8(registers::ACCEL_CONFIG2, new_value).await
+    }
+
+    pub async fn get_accel_bandwidth(&mut self) -> Result<AccelBandwidth, Error<I2C::Error>> {
+        self.model.require(Feature::AccelBandwidth)?;
+        let raw_value = self.read_u8(registers::ACCEL_CONFIG2).await?;
+        Ok(AccelBandwidth::from_reg(raw_value))
+    }
+
+    /// Returns measured acceleration, (X, Y, Z), in m/s².
+    pub async fn acceleration(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        let raw = self.acceleration_raw().await?;
+        Ok(self.scale_acceleration(raw))
+    }
+
+    /// Same as [`Mpu6886Async::acceleration`], in m/s².
+    pub async fn acceleration_ms2(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        self.acceleration().await
+    }
+
+    /// Returns measured acceleration, (X, Y, Z), in g.
+    pub async fn acceleration_g(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        let (x, y, z) = self.acceleration().await?;
+        Ok((x / GRAVITY, y / GRAVITY, z / GRAVITY))
+    }
+
+    /// Returns raw accelerometer readings, (X, Y, Z), in LSB of the current
+    /// scale range.
+    pub async fn acceleration_raw(&mut self) -> Result<(i16, i16, i16), Error<I2C::Error>> {
+        self.check_ready()?;
+        let mut xyz_buf: [u8; 6] = [0; 6];
+        self.read_buf(registers::ACCEL_XOUT_H, &mut xyz_buf).await?;
+        Ok(xyz_from_be_bytes(&xyz_buf))
+    }
+
+    /// Returns the scale range cached by the driver, without bus traffic.
+    pub fn gyro_scale_range(&self) -> GyroScaleRange {
+        self.gyro_range
+    }
+
+    pub async fn get_gyro_scale_range(&mut self) -> Result<GyroScaleRange, Error<I2C::Error>> {
+        let raw_value = self.read_u8(registers::GYRO_CONFIG).await?;
+        Ok(GyroScaleRange::from_reg(raw_value))
+    }
+
+    pub async fn set_gyro_scale_range(
+        &mut self,
+        value: GyroScaleRange,
+    ) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::GYRO_CONFIG).await?;
+        let reg_value = value.apply_to_reg(original_value);
+        self.write_u8(registers::GYRO_CONFIG, reg_value).await?;
+        self.gyro_range = value;
+        Ok(())
+    }
+
+    #[deprecated(note = "use `set_power_mode(PowerMode::Standby)`")]
+    pub async fn gyro_standby(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.model.require(Feature::GyroStandby)?;
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value | pwr_mgmt_1::GYRO_STANDBY;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    #[deprecated(note = "use `set_power_mode`")]
+    pub async fn gyro_activate(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1).await?;
+        let new_value = original_value & !pwr_mgmt_1::GYRO_STANDBY;
+        self.write_u8(registers::PWR_MGMT_1, new_value).await
+    }
+
+    /// Returns measured angular acceleration, (X, Y, Z), in rad/s
+    pub async fn gyro(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        let raw = self.gyro_raw().await?;
+        Ok(self.conversion().gyro(raw))
+    }
+
+    /// Same as [`Mpu6886Async::gyro`], in rad/s.
+    pub async fn gyro_rads(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        self.gyro().await
+    }
+
+    /// Returns measured angular rate, (X, Y, Z), in °/s.
+    pub async fn gyro_dps(&mut self) -> Result<(f32, f32, f32), Error<I2C::Error>> {
+        let (x, y, z) = self.gyro().await?;
+        Ok((x.to_degrees(), y.to_degrees(), z.to_degrees()))
+    }
+
+    /// Returns raw gyroscope readings, (X, Y, Z), in LSB of the current
+    /// scale range.
+    pub async fn gyro_raw(&mut self) -> Result<(i16, i16, i16), Error<I2C::Error>> {
+        self.check_ready()?;
+        let mut xyz_buf: [u8; 6] = [0; 6];
+        self.read_buf(registers::GYRO_XOUT_H, &mut xyz_buf).await?;
+        Ok(xyz_from_be_bytes(&xyz_buf))
+    }
+
+    /// Fires the INT pin every time new sensor data is available.
+    pub async fn enable_data_ready_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::INT_ENABLE).await?;
+        let new_value = original_value | int_enable::DATA_RDY_INT_EN;
+        self.write_u8(registers::INT_ENABLE, new_value).await
+    }
+
+    pub async fn disable_data_ready_interrupt(&mut self) -> Result<(), Error<I2C::Error>> {
+        let original_value = self.read_u8(registers::INT_ENABLE).await?;
+        let new_value = original_value & !int_enable::DATA_RDY_INT_EN;
+        self.write_u8(registers::INT_ENABLE, new_value).await
+    }
+
+    /// Returns whether the INT pin is configured active low.
+    async fn interrupt_active_low(&mut self) -> Result<bool, Error<I2C::Error>> {
+        let raw_value = self.read_u8(registers::INT_PIN_CFG).await?;
+        Ok(InterruptPinConfig::from_reg(raw_value).level == InterruptLevel::ActiveLow)
+    }
+
+    /// Waits until `int_pin` is at its active level, then reads and clears
+    /// INT_STATUS.
+    ///
+    /// Waiting on the level instead of an edge also catches an interrupt
+    /// that fired before the wait started, or a latched pin still held.
+    async fn wait_for_interrupt(
+        &mut self,
+        int_pin: &mut impl Wait,
+        active_low: bool,
+    ) -> Result<InterruptStatus, Error<I2C::Error>> {
+        let level = if active_low {
+            int_pin.wait_for_low().await
+        } else {
+            int_pin.wait_for_high().await
+        };
+        level.map_err(|_| Error::Other)?;
+        let raw_value = self.read_u8(registers::INT_STATUS).await?;
+        Ok(InterruptStatus::from_int_status(raw_value, self.model))
+    }
+
+    /// Waits until the wake-on-motion interrupt fires on `int_pin`, returns
+    /// the flags read from INT_STATUS, with the triggering axes.
+    ///
+    /// Enables the wake-on-motion logic and interrupt if needed, the
+    /// threshold set on the chip is kept. Waits on the INT pin level from
+    /// INT_PIN_CFG, so pulse and latched modes both work. Pin errors are
+    /// reported as [`Error::Other`].
+    pub async fn wait_for_motion(
+        &mut self,
+        int_pin: &mut impl Wait,
+    ) -> Result<InterruptStatus, Error<I2C::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        let original_value = self.read_u8(registers::ACCEL_INTEL_CTRL).await?;
+        let intel_bits = accel_intel_ctrl::ACCEL_INTEL_EN | accel_intel_ctrl::ACCEL_INTEL_MODE;
+        if original_value & intel_bits != intel_bits {
+            let new_value = original_value | intel_bits;
+            self.write_u8(registers::ACCEL_INTEL_CTRL, new_value)
+                .await?;
+        }
+        let wom_bits = self.model.wom_int_bits();
+        let original_value = self.read_u8(registers::INT_ENABLE).await?;
+        if original_value & wom_bits != wom_bits {
+            let new_value = original_value | wom_bits;
+            self.write_u8(registers::INT_ENABLE, new_value).await?;
+        }
+        let active_low = self.interrupt_active_low().await?;
+        // clear stale flags
+        self.read_u8(registers::INT_STATUS).await?;
+        loop {
+            let status = self.wait_for_interrupt(int_pin, active_low).await?;
+            if status.wake_on_motion() {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Turns the driver into a stream of measurements, one per data-ready
+    /// interrupt on `int_pin`.
+    ///
+    /// Expects the data-ready interrupt enabled. Waits on the INT pin level
+    /// from INT_PIN_CFG and clears INT_STATUS for every sample, so pulse and
+    /// latched modes both work. Pin errors are reported as [`Error::Other`].
+    #[cfg(feature = "stream")]
+    pub fn into_stream<P: Wait>(
+        self,
+        int_pin: P,
+    ) -> impl Stream<Item = Result<Measurement, Error<I2C::Error>>> {
+        futures::stream::unfold(
+            (self, int_pin, None),
+            |(mut sensor, mut int_pin, mut active_low)| async move {
+                let measurement = sensor.next_sample(&mut int_pin, &mut active_low).await;
+                Some((measurement, (sensor, int_pin, active_low)))
+            },
+        )
+    }
+
+    /// Waits for the next data-ready interrupt and reads the sample, reading
+    /// the INT pin level on first use.
+    #[cfg(feature = "stream")]
+    async fn next_sample(
+        &mut self,
+        int_pin: &mut impl Wait,
+        active_low: &mut Option<bool>,
+    ) -> Result<Measurement, Error<I2C::Error>> {
+        let active_low = match *active_low {
+            Some(value) => value,
+            None => *active_low.insert(self.interrupt_active_low().await?),
+        };
+        loop {
+            let status = self.wait_for_interrupt(int_pin, active_low).await?;
+            if status.contains(InterruptStatus::DATA_READY) {
+                return self.read_all().await;
+            }
+        }
+    }
+
+    /// Reads accelerometer, temperature and gyroscope in one transaction.
+    pub async fn read_all(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        let raw = self.read_all_raw().await?;
+        Ok(self.conversion().measurement(raw))
+    }
+
+    pub async fn read_all_raw(&mut self) -> Result<RawMeasurement, Error<I2C::Error>> {
+        self.check_ready()?;
+        let mut frame = RawAccelGyroFrame::default();
+        self.read_buf(RawAccelGyroFrame::BASE_REGISTER, &mut frame.0)
+            .await?;
+        Ok(frame.into())
+    }
+
+    /// Fails unless the driver is initialized and the chip awake, like the
+    /// blocking driver.
+    fn check_ready(&self) -> Result<(), Error<I2C::Error>> {
+        check_ready(self.initialized, self.asleep)
+    }
+
+    /// Tracks the power state from PWR_MGMT_1 writes, a reset also drops the
+    /// loaded state.
+    fn track_write(&mut self, reg: u8, value: u8) {
+        if reg == registers::PWR_MGMT_1 {
+            self.asleep = value & (pwr_mgmt_1::DEVICE_RESET | pwr_mgmt_1::SLEEP) != 0;
+            if value & pwr_mgmt_1::DEVICE_RESET != 0 {
+                self.initialized = false;
+            }
+        }
+    }
+
+    async fn read_u8(&mut self, reg: u8) -> Result<u8, Error<I2C::Error>> {
+        let mut buf: [u8; 1] = [0; 1];
+        self.read_buf(reg, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    async fn write_u8(&mut self, reg: u8, value: u8) -> Result<(), Error<I2C::Error>> {
+        #[cfg(feature = "trace")]
+        crate::trace_write(reg, value);
+        self.i2c
+            .write(self.address, &[reg, value])
+            .await
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            })?;
+        self.track_write(reg, value);
+        Ok(())
+    }
+
+    #[inline]
+    async fn read_buf(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        let max_len = self.max_read_len.unwrap_or(buf.len()).max(1);
+        for (index, chunk) in buf.chunks_mut(max_len).enumerate() {
+            let chunk_reg = chunk_register(reg, index, max_len)?;
+            self.i2c
+                .write_read(self.address, &[chunk_reg], chunk)
+                .await
+                .map_err(|error| Error::Transfer {
+                    operation: BusOperation::ReadReg(chunk_reg),
+                    error,
+                })?;
+        }
+        #[cfg(feature = "trace")]
+        crate::trace_read(reg, buf);
+        Ok(())
+    }
+}
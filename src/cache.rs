@@ -0,0 +1,73 @@
+//! Register cache implementation.
+
+use crate::{registers, Interface, Mpu6886};
+
+/// Registers kept in the cache.
+const CACHED_REGISTERS: [u8; 5] = [
+    registers::CONFIG,
+    registers::GYRO_CONFIG,
+    registers::ACCEL_CONFIG,
+    registers::PWR_MGMT_1,
+    registers::PWR_MGMT_2,
+];
+
+/// Write-through cache of the configuration registers most setters modify.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) struct RegisterCache {
+    enabled: bool,
+    values: [Option<u8>; 5],
+}
+
+impl RegisterCache {
+    fn slot(reg: u8) -> Option<usize> {
+        CACHED_REGISTERS.iter().position(|&cached| cached == reg)
+    }
+
+    /// Returns the cached value of `reg`, if any.
+    pub(crate) fn get(&self, reg: u8) -> Option<u8> {
+        if !self.enabled {
+            return None;
+        }
+        Self::slot(reg).and_then(|slot| self.values[slot])
+    }
+
+    /// Records a value read from or written to `reg`.
+    pub(crate) fn update(&mut self, reg: u8, value: u8) {
+        if reg == registers::PWR_MGMT_1 && value & registers::pwr_mgmt_1::DEVICE_RESET != 0 {
+            // device reset, everything goes back to defaults
+            self.invalidate();
+            return;
+        }
+        if self.enabled {
+            if let Some(slot) = Self::slot(reg) {
+                self.values[slot] = Some(value);
+            }
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.values = [None; 5];
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Caches CONFIG, GYRO_CONFIG, ACCEL_CONFIG and PWR_MGMT_1/2, so setters
+    /// of these registers only write.
+    ///
+    /// Only valid while nothing else writes these registers, e.g. another
+    /// driver instance.
+    pub fn enable_register_cache(&mut self) {
+        self.cache.enabled = true;
+    }
+
+    pub fn disable_register_cache(&mut self) {
+        self.cache.enabled = false;
+        self.cache.invalidate();
+    }
+
+    /// Drops the cached values, they are read from the chip again on next
+    /// use.
+    pub fn invalidate_register_cache(&mut self) {
+        self.cache.invalidate();
+    }
+}
@@ -0,0 +1,466 @@
+//! Calibration implementation.
+
+use crate::accelerometer::GRAVITY;
+use crate::{AccelOffsets, Error, GyroOffsets, Interface, Mpu6886};
+use core::f32::consts::PI;
+
+use embedded_hal::delay::DelayNs;
+
+/// Software calibration applied to converted readings.
+///
+/// Each axis is corrected as `(value - bias) * scale`. With the `serde`
+/// feature this can be (de)serialized, e.g. with postcard, to persist it in
+/// flash.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CalibrationData {
+    /// Gyroscope bias, (X, Y, Z), in rad/s.
+    pub gyro_bias: (f32, f32, f32),
+    /// Gyroscope scale correction, (X, Y, Z).
+    pub gyro_scale: (f32, f32, f32),
+    /// Accelerometer bias, (X, Y, Z), in m/s².
+    pub accel_bias: (f32, f32, f32),
+    /// Accelerometer scale correction, (X, Y, Z).
+    pub accel_scale: (f32, f32, f32),
+}
+
+impl Default for CalibrationData {
+    /// No correction.
+    fn default() -> Self {
+        Self {
+            gyro_bias: (0.0, 0.0, 0.0),
+            gyro_scale: (1.0, 1.0, 1.0),
+            accel_bias: (0.0, 0.0, 0.0),
+            accel_scale: (1.0, 1.0, 1.0),
+        }
+    }
+}
+
+fn correct(
+    value: (f32, f32, f32),
+    bias: (f32, f32, f32),
+    scale: (f32, f32, f32),
+) -> (f32, f32, f32) {
+    (
+        (value.0 - bias.0) * scale.0,
+        (value.1 - bias.1) * scale.1,
+        (value.2 - bias.2) * scale.2,
+    )
+}
+
+impl CalibrationData {
+    /// Corrects an acceleration, in m/s².
+    pub fn apply_accel(&self, value: (f32, f32, f32)) -> (f32, f32, f32) {
+        correct(value, self.accel_bias, self.accel_scale)
+    }
+
+    /// Corrects an angular rate, in rad/s.
+    pub fn apply_gyro(&self, value: (f32, f32, f32)) -> (f32, f32, f32) {
+        correct(value, self.gyro_bias, self.gyro_scale)
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Sets the software calibration applied to [`Mpu6886::acceleration`]
+    /// and [`Mpu6886::gyro`].
+    pub fn set_calibration(&mut self, calibration: CalibrationData) {
+        self.calibration = calibration;
+    }
+
+    pub fn calibration(&self) -> CalibrationData {
+        self.calibration
+    }
+
+    /// Measures the gyroscope bias by averaging `samples` readings, 1 ms
+    /// apart, and returns the hardware offsets cancelling it.
+    ///
+    /// The device must be held still. The current offsets are taken into
+    /// account, write the result with [`Mpu6886::set_gyro_offsets`] to
+    /// apply it.
+    pub fn calibrate_gyro(
+        &mut self,
+        delay: &mut impl DelayNs,
+        samples: u16,
+    ) -> Result<GyroOffsets, Error<DI::Error>> {
+        let (_, bias) = self.average_raw(delay, samples)?;
+        let current = self.get_gyro_offsets()?;
+        // offset LSB = OFFS_USR * 4 / 2 ^ FS_SEL
+        let factor = 1 << (self.gyro_range as i32);
+        let offset = |current: i16, bias: i32| {
+            (current as i32 - bias * factor / 4).clamp(i16::MIN as i32, i16::MAX as i32) as i16
+        };
+        Ok(GyroOffsets {
+            x: offset(current.x, bias.0),
+            y: offset(current.y, bias.1),
+            z: offset(current.z, bias.2),
+        })
+    }
+
+    /// Measures the accelerometer bias by averaging `samples` readings, 1 ms
+    /// apart, and returns the hardware offsets cancelling it.
+    ///
+    /// The device must be held still and level, with Z pointing up so it
+    /// reads 1 g. The current offsets are taken into account, write the
+    /// result with [`Mpu6886::set_accel_offsets`] to apply it.
+    pub fn calibrate_accel(
+        &mut self,
+        delay: &mut impl DelayNs,
+        samples: u16,
+    ) -> Result<AccelOffsets, Error<DI::Error>> {
+        let (average, _) = self.average_raw(delay, samples)?;
+        let current = self.get_accel_offsets()?;
+        let lsb_per_g = self.acc_range.lsb_per_g_i32();
+        let bias = (average.0, average.1, average.2 - lsb_per_g);
+        // one offset LSB is 1/1024 g
+        let offset = |current: i16, bias: i32| {
+            (current as i32 - bias * 1024 / lsb_per_g).clamp(-16384, 16383) as i16
+        };
+        Ok(AccelOffsets {
+            x: offset(current.x, bias.0),
+            y: offset(current.y, bias.1),
+            z: offset(current.z, bias.2),
+        })
+    }
+}
+
+/// Device orientations of [`SixPositionCalibration`], named by the axis
+/// pointing up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CalibrationPosition {
+    ZUp,
+    ZDown,
+    YUp,
+    YDown,
+    XUp,
+    XDown,
+}
+
+const POSITIONS: [CalibrationPosition; 6] = [
+    CalibrationPosition::ZUp,
+    CalibrationPosition::ZDown,
+    CalibrationPosition::YUp,
+    CalibrationPosition::YDown,
+    CalibrationPosition::XUp,
+    CalibrationPosition::XDown,
+];
+
+/// Accelerometer bias and scale found by [`SixPositionCalibration`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccelCalibration {
+    /// Bias, (X, Y, Z), in m/s².
+    pub bias: (f32, f32, f32),
+    /// Scale correction, (X, Y, Z).
+    pub scale: (f32, f32, f32),
+}
+
+impl AccelCalibration {
+    /// Sets the accelerometer bias and scale of `calibration`, for software
+    /// correction.
+    pub fn apply_to(&self, calibration: &mut CalibrationData) {
+        calibration.accel_bias = self.bias;
+        calibration.accel_scale = self.scale;
+    }
+
+    /// Returns the hardware offsets cancelling the bias, given the offsets
+    /// that were set during the calibration. The scale can only be corrected
+    /// in software.
+    pub fn hardware_offsets(&self, current: AccelOffsets) -> AccelOffsets {
+        // one offset LSB is 1/1024 g
+        let offset = |current: i16, bias: f32| {
+            (current as f32 - bias / GRAVITY * 1024.0).clamp(-16384.0, 16383.0) as i16
+        };
+        AccelOffsets {
+            x: offset(current.x, self.bias.0),
+            y: offset(current.y, self.bias.1),
+            z: offset(current.z, self.bias.2),
+        }
+    }
+}
+
+/// Guided six-position accelerometer calibration.
+///
+/// Each axis is measured pointing up and down, giving its bias and scale:
+///
+/// ```rust,ignore
+/// let mut calibration = SixPositionCalibration::new();
+/// while let Some(position) = calibration.next_position() {
+///     // ask the user to hold the device still in `position`
+///     calibration.record(&mut sensor, &mut delay, 500)?;
+/// }
+/// let result = calibration.finish().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SixPositionCalibration {
+    /// Averages in m/s², in the order of [`POSITIONS`].
+    readings: [(f32, f32, f32); 6],
+    recorded: usize,
+}
+
+impl SixPositionCalibration {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the position to record next, `None` once all are recorded.
+    pub fn next_position(&self) -> Option<CalibrationPosition> {
+        POSITIONS.get(self.recorded).copied()
+    }
+
+    /// Averages `samples` readings, 1 ms apart, for the current position.
+    ///
+    /// The readings are taken without software calibration and mounting
+    /// correction. Does nothing once all positions are recorded.
+    pub fn record<DI: Interface>(
+        &mut self,
+        sensor: &mut Mpu6886<DI>,
+        delay: &mut impl DelayNs,
+        samples: u16,
+    ) -> Result<(), Error<DI::Error>> {
+        if self.recorded == POSITIONS.len() {
+            return Ok(());
+        }
+        let (average, _) = sensor.average_raw(delay, samples)?;
+        let factor = GRAVITY / sensor.acc_range.lsb_per_g();
+        self.readings[self.recorded] = (
+            average.0 as f32 * factor,
+            average.1 as f32 * factor,
+            average.2 as f32 * factor,
+        );
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Returns the bias and scale, `None` until all positions are recorded.
+    pub fn finish(&self) -> Option<AccelCalibration> {
+        if self.recorded < POSITIONS.len() {
+            return None;
+        }
+        let [z_up, z_down, y_up, y_down, x_up, x_down] = self.readings;
+        let bias = |up: f32, down: f32| (up + down) / 2.0;
+        let scale = |up: f32, down: f32| 2.0 * GRAVITY / (up - down);
+        Some(AccelCalibration {
+            bias: (
+                bias(x_up.0, x_down.0),
+                bias(y_up.1, y_down.1),
+                bias(z_up.2, z_down.2),
+            ),
+            scale: (
+                scale(x_up.0, x_down.0),
+                scale(y_up.1, y_down.1),
+                scale(z_up.2, z_down.2),
+            ),
+        })
+    }
+}
+
+/// Gyroscope bias measured at up to 8 temperatures, see
+/// [`GyroTemperatureTable::record`].
+///
+/// The bias in between is interpolated, outside the measured range the
+/// nearest point is used. Deserializing rejects tables with more than 8
+/// points or unsorted temperatures.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "GyroTemperatureTableData")
+)]
+pub struct GyroTemperatureTable {
+    /// (temperature in °C, bias in rad/s), sorted by temperature.
+    points: [(f32, (f32, f32, f32)); 8],
+    len: usize,
+}
+
+/// Unchecked serialized form of [`GyroTemperatureTable`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GyroTemperatureTableData {
+    points: [(f32, (f32, f32, f32)); 8],
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GyroTemperatureTableData> for GyroTemperatureTable {
+    type Error = &'static str;
+
+    fn try_from(data: GyroTemperatureTableData) -> Result<Self, Self::Error> {
+        let points = data
+            .points
+            .get(..data.len)
+            .ok_or("more than 8 points in the gyroscope temperature table")?;
+        if !points.windows(2).all(|pair| pair[0].0 <= pair[1].0) {
+            return Err("gyroscope temperature table not sorted by temperature");
+        }
+        Ok(Self {
+            points: data.points,
+            len: data.len,
+        })
+    }
+}
+
+impl GyroTemperatureTable {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded points, (temperature in °C, bias in rad/s).
+    pub fn points(&self) -> &[(f32, (f32, f32, f32))] {
+        &self.points[..self.len]
+    }
+
+    /// Adds a point, returns `false` if the table is full.
+    pub fn add_point(&mut self, temperature: f32, bias: (f32, f32, f32)) -> bool {
+        if self.len == self.points.len() {
+            return false;
+        }
+        let index = self.points[..self.len].partition_point(|(t, _)| *t < temperature);
+        self.points.copy_within(index..self.len, index + 1);
+        self.points[index] = (temperature, bias);
+        self.len += 1;
+        true
+    }
+
+    /// Measures the gyroscope bias and temperature by averaging `samples`
+    /// readings, 1 ms apart, and adds them as a point.
+    ///
+    /// The device must be held still, call this at several temperatures
+    /// during a calibration run. Returns `false` if the table is full.
+    pub fn record<DI: Interface>(
+        &mut self,
+        sensor: &mut Mpu6886<DI>,
+        delay: &mut impl DelayNs,
+        samples: u16,
+    ) -> Result<bool, Error<DI::Error>> {
+        let temperature = sensor.temperature()?;
+        let (_, average) = sensor.average_raw(delay, samples)?;
+        let factor = PI / 180.0 / sensor.gyro_range.lsb_per_dps();
+        let bias = (
+            average.0 as f32 * factor,
+            average.1 as f32 * factor,
+            average.2 as f32 * factor,
+        );
+        Ok(self.add_point(temperature, bias))
+    }
+
+    /// Returns the interpolated bias at `temperature`, in rad/s.
+    pub fn bias_at(&self, temperature: f32) -> (f32, f32, f32) {
+        let points = self.points();
+        let index = points.partition_point(|(t, _)| *t < temperature);
+        match (index.checked_sub(1).map(|i| points[i]), points.get(index)) {
+            (Some((t0, b0)), Some(&(t1, b1))) => {
+                let k = (temperature - t0) / (t1 - t0);
+                (
+                    b0.0 + (b1.0 - b0.0) * k,
+                    b0.1 + (b1.1 - b0.1) * k,
+                    b0.2 + (b1.2 - b0.2) * k,
+                )
+            }
+            (Some((_, bias)), None) | (None, Some(&(_, bias))) => bias,
+            (None, None) => (0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads the temperature and sets the software gyroscope bias from
+    /// `table`, returns the temperature.
+    ///
+    /// Call this periodically as the temperature drifts.
+    pub fn apply_gyro_temperature_table(
+        &mut self,
+        table: &GyroTemperatureTable,
+    ) -> Result<f32, Error<DI::Error>> {
+        let temperature = self.temperature()?;
+        self.calibration.gyro_bias = table.bias_at(temperature);
+        Ok(temperature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> GyroTemperatureTable {
+        let mut table = GyroTemperatureTable::new();
+        table.add_point(40.0, (0.2, 0.0, 0.0));
+        table.add_point(20.0, (0.1, 0.0, 0.0));
+        table
+    }
+
+    #[test]
+    fn points_are_sorted_by_temperature() {
+        let mut table = table();
+        assert!(table.add_point(30.0, (0.0, 0.0, 0.3)));
+        let temperatures = table.points().iter().map(|(t, _)| *t);
+        assert!(temperatures.eq([20.0, 30.0, 40.0]));
+    }
+
+    #[test]
+    fn full_table_rejects_points() {
+        let mut table = GyroTemperatureTable::new();
+        for i in 0..8 {
+            assert!(table.add_point(i as f32, (0.0, 0.0, 0.0)));
+        }
+        assert!(!table.add_point(8.0, (0.0, 0.0, 0.0)));
+        assert_eq!(table.points().len(), 8);
+    }
+
+    #[test]
+    fn bias_is_interpolated_between_points() {
+        let table = table();
+        let (x, y, z) = table.bias_at(25.0);
+        assert!((x - 0.125).abs() < 1e-6);
+        assert_eq!((y, z), (0.0, 0.0));
+        assert_eq!(table.bias_at(20.0), (0.1, 0.0, 0.0));
+        assert_eq!(table.bias_at(40.0), (0.2, 0.0, 0.0));
+    }
+
+    #[test]
+    fn bias_is_clamped_outside_of_points() {
+        let table = table();
+        assert_eq!(table.bias_at(-10.0), (0.1, 0.0, 0.0));
+        assert_eq!(table.bias_at(85.0), (0.2, 0.0, 0.0));
+    }
+
+    #[test]
+    fn empty_table_has_no_bias() {
+        assert_eq!(GyroTemperatureTable::new().bias_at(25.0), (0.0, 0.0, 0.0));
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn table_round_trips_through_postcard() {
+        let mut buf = [0; 160];
+        let bytes = postcard::to_slice(&table(), &mut buf).unwrap();
+        let decoded: GyroTemperatureTable = postcard::from_bytes(bytes).unwrap();
+        assert_eq!(decoded, table());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn table_with_too_many_points_is_rejected() {
+        let mut buf = [0; 160];
+        let bytes = postcard::to_slice(&table(), &mut buf).unwrap();
+        // len is the trailing varint
+        *bytes.last_mut().unwrap() = 9;
+        assert!(postcard::from_bytes::<GyroTemperatureTable>(bytes).is_err());
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn unsorted_table_is_rejected() {
+        let mut buf = [0; 160];
+        let bytes = postcard::to_slice(&table(), &mut buf).unwrap();
+        // swap the temperatures of both points, 16 bytes each
+        let (first, second) = bytes.split_at_mut(16);
+        first[..4].swap_with_slice(&mut second[..4]);
+        assert!(postcard::from_bytes::<GyroTemperatureTable>(bytes).is_err());
+    }
+}
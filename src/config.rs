@@ -0,0 +1,375 @@
+//! One-shot configuration implementation.
+
+use crate::model::Feature;
+use crate::rate::internal_sample_rate;
+use crate::{
+    registers, AccelBandwidth, AccelScaleRange, ClockSource, Error, ExtSync, FifoConfig, FifoMode,
+    GyroBandwidth, GyroScaleRange, I2c, I2cInterface, Interface, InterruptPinConfig, Mpu6886,
+    OutputDataRate, PowerMode,
+};
+
+use embedded_hal::delay::DelayNs;
+
+/// Full sensor configuration, applied with [`Mpu6886::apply_config`].
+///
+/// The default is a general purpose setup: ±4 g, ±500 dps, 100 Hz output
+/// data rate with matching DLPF settings, the best available clock and the
+/// temperature sensor on. [`Mpu6886Config::reset_state`] matches the chip's
+/// reset state instead. Use the `with_*` methods to build a config:
+///
+/// ```rust,ignore
+/// let config = Mpu6886Config::default()
+///     .with_accel_range(AccelScaleRange::Range8g)
+///     .with_gyro_range(GyroScaleRange::Range2000Dps)
+///     .with_gyro_bandwidth(GyroBandwidth::Hz92)
+///     .with_sample_rate_divider(4);
+/// let mut sensor = Mpu6886::with_config(i2c, config, &mut delay)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mpu6886Config {
+    pub accel_range: AccelScaleRange,
+    pub gyro_range: GyroScaleRange,
+    /// Ignored on the MPU6050, which has no accelerometer DLPF.
+    pub accel_bandwidth: AccelBandwidth,
+    pub gyro_bandwidth: GyroBandwidth,
+    /// Output data rate is `internal rate / (1 + divider)`, a non-zero
+    /// divider needs a gyroscope bandwidth of 176 Hz or less.
+    pub sample_rate_divider: u8,
+    pub clock_source: ClockSource,
+    pub temperature_sensor: bool,
+    pub interrupt_pin: InterruptPinConfig,
+    pub data_ready_interrupt: bool,
+    /// FIFO data sources, `None` disables the FIFO.
+    pub fifo: Option<FifoConfig>,
+    pub fifo_mode: FifoMode,
+    pub ext_sync: ExtSync,
+}
+
+impl Default for Mpu6886Config {
+    fn default() -> Self {
+        let rate = OutputDataRate::Hz100;
+        let (gyro_bandwidth, accel_bandwidth) = rate.bandwidths();
+        Self {
+            accel_range: AccelScaleRange::Range4g,
+            gyro_range: GyroScaleRange::Range500Dps,
+            accel_bandwidth,
+            gyro_bandwidth,
+            sample_rate_divider: rate.divider(),
+            clock_source: ClockSource::AutoSelect,
+            ..Self::reset_state()
+        }
+    }
+}
+
+impl Mpu6886Config {
+    /// Returns the configuration the chip has after a reset.
+    #[must_use]
+    pub fn reset_state() -> Self {
+        Self {
+            accel_range: AccelScaleRange::Range2g,
+            gyro_range: GyroScaleRange::Range250Dps,
+            accel_bandwidth: AccelBandwidth::Hz218,
+            gyro_bandwidth: GyroBandwidth::Hz250,
+            sample_rate_divider: 0,
+            clock_source: ClockSource::Internal,
+            temperature_sensor: true,
+            interrupt_pin: InterruptPinConfig::default(),
+            data_ready_interrupt: false,
+            fifo: None,
+            fifo_mode: FifoMode::Stream,
+            ext_sync: ExtSync::Disabled,
+        }
+    }
+
+    #[must_use]
+    pub fn with_accel_range(mut self, range: AccelScaleRange) -> Self {
+        self.accel_range = range;
+        self
+    }
+
+    #[must_use]
+    pub fn with_gyro_range(mut self, range: GyroScaleRange) -> Self {
+        self.gyro_range = range;
+        self
+    }
+
+    #[must_use]
+    pub fn with_accel_bandwidth(mut self, bandwidth: AccelBandwidth) -> Self {
+        self.accel_bandwidth = bandwidth;
+        self
+    }
+
+    #[must_use]
+    pub fn with_gyro_bandwidth(mut self, bandwidth: GyroBandwidth) -> Self {
+        self.gyro_bandwidth = bandwidth;
+        self
+    }
+
+    #[must_use]
+    pub fn with_sample_rate_divider(mut self, divider: u8) -> Self {
+        self.sample_rate_divider = divider;
+        self
+    }
+
+    #[must_use]
+    pub fn with_clock_source(mut self, source: ClockSource) -> Self {
+        self.clock_source = source;
+        self
+    }
+
+    #[must_use]
+    pub fn with_temperature_sensor(mut self, enabled: bool) -> Self {
+        self.temperature_sensor = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_interrupt_pin(mut self, config: InterruptPinConfig) -> Self {
+        self.interrupt_pin = config;
+        self
+    }
+
+    #[must_use]
+    pub fn with_data_ready_interrupt(mut self, enabled: bool) -> Self {
+        self.data_ready_interrupt = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn with_fifo(mut self, fifo: Option<FifoConfig>) -> Self {
+        self.fifo = fifo;
+        self
+    }
+
+    #[must_use]
+    pub fn with_fifo_mode(mut self, mode: FifoMode) -> Self {
+        self.fifo_mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub fn with_ext_sync(mut self, ext_sync: ExtSync) -> Self {
+        self.ext_sync = ext_sync;
+        self
+    }
+}
+
+impl<I2C: I2c> Mpu6886<I2cInterface<I2C>> {
+    /// Creates a driver, checks the chip and applies `config`.
+    ///
+    /// Unlike [`Mpu6886::with_config`] the chip isn't reset and stays in its
+    /// current power state, after power-up it's asleep and reads fail with
+    /// [`Error::Asleep`] until [`Mpu6886::set_power_mode`] wakes it.
+    pub fn new_with_config(i2c: I2C, config: Mpu6886Config) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c);
+        sensor.init()?;
+        sensor.apply_config(&config)?;
+        Ok(sensor)
+    }
+
+    /// Creates a driver, resets the chip, waits until it's back, applies
+    /// `config` and wakes it up, ready for measurements.
+    ///
+    /// Use [`Mpu6886::new_with_config`] to keep the chip's power state and
+    /// skip the reset.
+    pub fn with_config(
+        i2c: I2C,
+        config: Mpu6886Config,
+        delay: &mut impl DelayNs,
+    ) -> Result<Self, Error<I2C::Error>> {
+        let mut sensor = Self::new(i2c);
+        sensor.reset_and_wait(delay)?;
+        sensor.apply_config(&config)?;
+        sensor.set_power_mode(PowerMode::SixAxisLowNoise)?;
+        Ok(sensor)
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads the current configuration from the chip.
+    ///
+    /// Together with [`Mpu6886::apply_config`] this saves and restores the
+    /// configuration, e.g. around a reset.
+    pub fn read_config(&mut self) -> Result<Mpu6886Config, Error<DI::Error>> {
+        let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1)?;
+        let int_enable = self.read_u8(registers::INT_ENABLE)?;
+        let user_ctrl = self.read_u8(registers::USER_CTRL)?;
+        let fifo = if user_ctrl & registers::user_ctrl::FIFO_EN != 0 {
+            Some(self.get_fifo_config()?)
+        } else {
+            None
+        };
+        Ok(Mpu6886Config {
+            accel_range: self.get_accel_scale_range()?,
+            gyro_range: self.get_gyro_scale_range()?,
+            accel_bandwidth: if self.model.supports(Feature::AccelBandwidth) {
+                self.get_accel_bandwidth()?
+            } else {
+                Mpu6886Config::reset_state().accel_bandwidth
+            },
+            gyro_bandwidth: self.get_gyro_bandwidth()?,
+            sample_rate_divider: self.get_sample_rate_divider()?,
+            clock_source: ClockSource::from_reg(pwr_mgmt_1),
+            temperature_sensor: pwr_mgmt_1 & registers::pwr_mgmt_1::TEMP_DIS == 0,
+            interrupt_pin: self.get_interrupt_pin_config()?,
+            data_ready_interrupt: int_enable & registers::int_enable::DATA_RDY_INT_EN != 0,
+            fifo,
+            fifo_mode: self.get_fifo_mode()?,
+            ext_sync: self.get_ext_sync()?,
+        })
+    }
+
+    /// Applies all settings of `config`, in the order the datasheet expects.
+    ///
+    /// The power state isn't changed. Fails with [`Error::InvalidConfig`] if
+    /// a sample rate divider is set with a gyroscope bandwidth that bypasses
+    /// it, or a setting needs a feature the chip lacks.
+    pub fn apply_config(&mut self, config: &Mpu6886Config) -> Result<(), Error<DI::Error>> {
+        let (dlpf_cfg, fchoice_b) = config.gyro_bandwidth.to_fields();
+        let (_, divider_applies) = internal_sample_rate(dlpf_cfg, fchoice_b);
+        if config.sample_rate_divider != 0 && !divider_applies {
+            return Err(Error::InvalidConfig(
+                "sample rate divider needs a gyroscope bandwidth of 176 Hz or less",
+            ));
+        }
+        if fchoice_b != 0 {
+            self.model.require(Feature::GyroDlpfBypass)?;
+        }
+        if config.fifo_mode == FifoMode::StopOnFull {
+            self.model.require(Feature::FifoMode)?;
+        }
+        self.set_clock_source(config.clock_source)?;
+        if config.temperature_sensor {
+            self.enable_temperature_sensor()?;
+        } else {
+            self.disable_temperature_sensor()?;
+        }
+        // SMPLRT_DIV, CONFIG, GYRO_CONFIG, ACCEL_CONFIG and ACCEL_CONFIG2 are
+        // contiguous, they are written in one transaction
+        let len = if self.model.supports(Feature::AccelBandwidth) {
+            5
+        } else {
+            4
+        };
+        let mut regs: [u8; 5] = [0; 5];
+        self.read_buf(registers::SMPLRT_DIV, &mut regs[..len])?;
+        let fifo_mode = match config.fifo_mode {
+            FifoMode::Stream => 0,
+            FifoMode::StopOnFull => registers::config::FIFO_MODE,
+        };
+        regs[0] = config.sample_rate_divider;
+        regs[1] = config.ext_sync.apply_to_reg(
+            regs[1] & !(registers::config::FIFO_MODE | registers::config::DLPF_CFG)
+                | fifo_mode
+                | dlpf_cfg,
+        );
+        regs[2] = config
+            .gyro_range
+            .apply_to_reg(regs[2] & !registers::gyro_config::FCHOICE_B | fchoice_b);
+        regs[3] = config.accel_range.apply_to_reg(regs[3]);
+        regs[4] = config.accel_bandwidth.apply_to_reg(regs[4]);
+        self.write_buf(registers::SMPLRT_DIV, &regs[..len])?;
+        self.acc_range = config.accel_range;
+        self.gyro_range = config.gyro_range;
+        self.set_interrupt_pin_config(config.interrupt_pin)?;
+        if config.data_ready_interrupt {
+            self.enable_data_ready_interrupt()?;
+        } else {
+            self.disable_data_ready_interrupt()?;
+        }
+        match config.fifo {
+            Some(fifo) => {
+                self.set_fifo_config(fifo)?;
+                self.enable_fifo()?;
+                self.reset_fifo()?;
+            }
+            None => self.disable_fifo()?,
+        }
+        self.last_config = Some(*config);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::MPU6886_DEFAULT_ADDR;
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+
+    fn read(reg: u8, value: &[u8]) -> Transaction {
+        Transaction::write_read(MPU6886_DEFAULT_ADDR, vec![reg], value.to_vec())
+    }
+
+    fn write(reg: u8, value: u8) -> Transaction {
+        Transaction::write(MPU6886_DEFAULT_ADDR, vec![reg, value])
+    }
+
+    #[test]
+    fn default_config_encoding() {
+        let config = Mpu6886Config::default();
+        assert_eq!(config.sample_rate_divider, 9);
+        assert_eq!(config.gyro_bandwidth.to_fields(), (3, 0));
+        assert_eq!(config.gyro_range.apply_to_reg(0), 0b00001000);
+        assert_eq!(config.accel_range.apply_to_reg(0), 0b00001000);
+        assert_eq!(config.accel_bandwidth.apply_to_reg(0), 3);
+        assert_eq!(ClockSource::from_reg(0b00000001), config.clock_source);
+    }
+
+    #[test]
+    fn apply_and_read_config_round_trip() {
+        let config = Mpu6886Config::default();
+        let expectations = [
+            // apply_config, starting from the reset state
+            read(registers::PWR_MGMT_1, &[0b01000000]),
+            write(registers::PWR_MGMT_1, 0b01000001),
+            read(registers::PWR_MGMT_1, &[0b01000001]),
+            write(registers::PWR_MGMT_1, 0b01000001),
+            read(registers::SMPLRT_DIV, &[0; 5]),
+            Transaction::transaction_start(MPU6886_DEFAULT_ADDR),
+            Transaction::write(MPU6886_DEFAULT_ADDR, vec![registers::SMPLRT_DIV]),
+            Transaction::write(MPU6886_DEFAULT_ADDR, vec![9, 3, 8, 8, 3]),
+            Transaction::transaction_end(MPU6886_DEFAULT_ADDR),
+            read(registers::INT_PIN_CFG, &[0]),
+            write(registers::INT_PIN_CFG, 0),
+            read(registers::INT_ENABLE, &[0]),
+            write(registers::INT_ENABLE, 0),
+            read(registers::USER_CTRL, &[0]),
+            write(registers::USER_CTRL, 0),
+            read(registers::FIFO_EN, &[0]),
+            write(registers::FIFO_EN, 0),
+            // read_config
+            read(registers::PWR_MGMT_1, &[0b01000001]),
+            read(registers::INT_ENABLE, &[0]),
+            read(registers::USER_CTRL, &[0]),
+            read(registers::ACCEL_CONFIG, &[8]),
+            read(registers::GYRO_CONFIG, &[8]),
+            read(registers::ACCEL_CONFIG2, &[3]),
+            read(registers::CONFIG, &[3, 8]),
+            read(registers::SMPLRT_DIV, &[9]),
+            read(registers::INT_PIN_CFG, &[0]),
+            read(registers::CONFIG, &[3]),
+            read(registers::CONFIG, &[3]),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        sensor.apply_config(&config).unwrap();
+        assert_eq!(sensor.read_config().unwrap(), config);
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn apply_config_rejects_bypassed_divider() {
+        let config = Mpu6886Config::default().with_gyro_bandwidth(GyroBandwidth::Hz250);
+        let mut sensor = Mpu6886::new(Mock::new(&[]));
+        assert!(matches!(
+            sensor.apply_config(&config),
+            Err(Error::InvalidConfig(_))
+        ));
+        sensor.destroy().done();
+    }
+}
@@ -0,0 +1,572 @@
+//! FIFO implementation.
+
+use crate::model::Feature;
+use crate::{registers, xyz_from_be_bytes, Error, Interface, Model, Mpu6886};
+
+/// Selects which sensor data is pushed into the FIFO.
+///
+/// The MPU6886 always writes temperature data along with the enabled
+/// sources, the driver enables it the same way on other models, so a packet
+/// contains temperature whenever any source is enabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FifoConfig {
+    pub accel: bool,
+    pub gyro: bool,
+}
+
+impl Default for FifoConfig {
+    fn default() -> Self {
+        Self {
+            accel: true,
+            gyro: true,
+        }
+    }
+}
+
+impl FifoConfig {
+    /// Returns whether any data is pushed into the FIFO.
+    pub fn is_empty(&self) -> bool {
+        !(self.accel || self.gyro)
+    }
+
+    /// Returns whether temperature data is part of every packet.
+    pub fn temp(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Returns the size of one FIFO packet in bytes.
+    pub fn packet_size(&self) -> usize {
+        let mut size = 0;
+        if self.accel {
+            size += 6;
+        }
+        if self.temp() {
+            size += 2;
+        }
+        if self.gyro {
+            size += 6;
+        }
+        size
+    }
+
+    /// Decodes raw FIFO data written with this config into samples.
+    pub fn parse(self, data: &[u8]) -> FifoPackets<'_> {
+        FifoPackets { data, config: self }
+    }
+
+    /// Decodes the config from a FIFO_EN register value of `model`.
+    pub(crate) fn from_reg(raw_value: u8, model: Model) -> Self {
+        let (accel, gyro, _) = model.fifo_en_bits();
+        Self {
+            accel: raw_value & accel == accel,
+            gyro: raw_value & gyro == gyro,
+        }
+    }
+
+    /// Encodes the config into a FIFO_EN register value of `model`.
+    pub(crate) fn apply_to_reg(self, original_value: u8, model: Model) -> u8 {
+        let (accel, gyro, temp) = model.fifo_en_bits();
+        let mut value = original_value & !(accel | gyro | temp);
+        if self.accel {
+            value |= accel;
+        }
+        if self.gyro {
+            value |= gyro;
+        }
+        if self.temp() {
+            value |= temp;
+        }
+        value
+    }
+}
+
+/// Behavior of the FIFO when it is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FifoMode {
+    /// New data overwrites the oldest data.
+    Stream,
+    /// New data is dropped until the FIFO is read or reset.
+    StopOnFull,
+}
+
+/// One decoded FIFO packet, with raw readings of the enabled sources.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FifoSample {
+    pub accel: Option<(i16, i16, i16)>,
+    pub gyro: Option<(i16, i16, i16)>,
+    pub temp: Option<i16>,
+}
+
+impl FifoSample {
+    /// Decodes one packet. `packet` must be exactly one packet long.
+    fn decode(packet: &[u8], config: FifoConfig) -> Self {
+        // packets follow the register order: accel, temperature, gyro
+        let mut sample = FifoSample::default();
+        let mut rest = packet;
+        if config.accel {
+            let (accel, tail) = rest.split_at(6);
+            sample.accel = Some(xyz_from_be_bytes(accel.try_into().unwrap()));
+            rest = tail;
+        }
+        if config.temp() {
+            let (temp, tail) = rest.split_at(2);
+            sample.temp = Some(i16::from_be_bytes([temp[0], temp[1]]));
+            rest = tail;
+        }
+        if config.gyro {
+            sample.gyro = Some(xyz_from_be_bytes(rest[..6].try_into().unwrap()));
+        }
+        sample
+    }
+}
+
+/// Iterator over the complete packets in a chunk of raw FIFO data.
+///
+/// Created by [`FifoConfig::parse`] or [`Mpu6886::parse_fifo`].
+#[derive(Debug, Clone)]
+pub struct FifoPackets<'a> {
+    data: &'a [u8],
+    config: FifoConfig,
+}
+
+impl<'a> FifoPackets<'a> {
+    /// Returns the trailing bytes that don't form a complete packet.
+    pub fn remainder(&self) -> &'a [u8] {
+        let size = self.config.packet_size();
+        if size == 0 {
+            self.data
+        } else {
+            &self.data[self.data.len() - self.data.len() % size..]
+        }
+    }
+}
+
+impl Iterator for FifoPackets<'_> {
+    type Item = FifoSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let size = self.config.packet_size();
+        if size == 0 || self.data.len() < size {
+            return None;
+        }
+        let (packet, rest) = self.data.split_at(size);
+        self.data = rest;
+        Some(FifoSample::decode(packet, self.config))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self
+            .data
+            .len()
+            .checked_div(self.config.packet_size())
+            .unwrap_or(0);
+        (count, Some(count))
+    }
+}
+
+impl ExactSizeIterator for FifoPackets<'_> {}
+
+/// Chunk size for [`FifoSamples`], a whole number of packets of every layout.
+const FIFO_CHUNK_SIZE: usize = 112;
+
+/// Iterator draining the FIFO, created by [`Mpu6886::fifo_samples`].
+///
+/// Reads whole packets in chunks as needed and ends once less than a packet
+/// is left, or after the first error.
+#[derive(Debug)]
+pub struct FifoSamples<'a, DI> {
+    driver: &'a mut Mpu6886<DI>,
+    buf: [u8; FIFO_CHUNK_SIZE],
+    pos: usize,
+    len: usize,
+    done: bool,
+}
+
+impl<DI: Interface> FifoSamples<'_, DI> {
+    /// Reads the next chunk of whole packets, returns false if none are left.
+    fn refill(&mut self, size: usize) -> Result<bool, Error<DI::Error>> {
+        let count = self.driver.fifo_count()? as usize;
+        let len = count.min(FIFO_CHUNK_SIZE) / size * size;
+        if len > 0 {
+            self.driver
+                .read_buf(registers::FIFO_R_W, &mut self.buf[..len])?;
+        }
+        self.pos = 0;
+        self.len = len;
+        Ok(len > 0)
+    }
+}
+
+impl<DI: Interface> Iterator for FifoSamples<'_, DI> {
+    type Item = Result<FifoSample, Error<DI::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let config = self.driver.fifo_config;
+        let size = config.packet_size();
+        if self.done || size == 0 {
+            return None;
+        }
+        if self.pos + size > self.len {
+            match self.refill(size) {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        let packet = &self.buf[self.pos..self.pos + size];
+        self.pos += size;
+        Some(Ok(FifoSample::decode(packet, config)))
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Decodes raw FIFO data using the current FIFO layout.
+    ///
+    /// `data` must start at a packet boundary, e.g. right after a FIFO reset.
+    pub fn parse_fifo<'a>(&self, data: &'a [u8]) -> FifoPackets<'a> {
+        self.fifo_config.parse(data)
+    }
+
+    /// Selects the data sources pushed into the FIFO.
+    ///
+    /// The driver remembers the layout to decode packets later.
+    pub fn set_fifo_config(&mut self, config: FifoConfig) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::FIFO_EN)?;
+        let new_value = config.apply_to_reg(original_value, self.model);
+        self.write_u8(registers::FIFO_EN, new_value)?;
+        self.fifo_config = config;
+        Ok(())
+    }
+
+    /// Reads the FIFO data sources configured on chip.
+    pub fn get_fifo_config(&mut self) -> Result<FifoConfig, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::FIFO_EN)?;
+        Ok(FifoConfig::from_reg(raw_value, self.model))
+    }
+
+    /// Returns the FIFO layout the driver uses for decoding.
+    pub fn fifo_config(&self) -> FifoConfig {
+        self.fifo_config
+    }
+
+    /// Enables the FIFO and starts pushing the data selected by
+    /// [`Mpu6886::set_fifo_config`] into it at the sample rate.
+    ///
+    /// By default accelerometer, temperature and gyroscope data are pushed.
+    pub fn enable_fifo(&mut self) -> Result<(), Error<DI::Error>> {
+        self.set_fifo_config(self.fifo_config)?;
+        let original_value = self.read_u8(registers::USER_CTRL)?;
+        let new_value = original_value | registers::user_ctrl::FIFO_EN;
+        self.write_u8(registers::USER_CTRL, new_value)
+    }
+
+    pub fn disable_fifo(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::USER_CTRL)?;
+        let new_value = original_value & !registers::user_ctrl::FIFO_EN;
+        self.write_u8(registers::USER_CTRL, new_value)?;
+        let original_value = self.read_u8(registers::FIFO_EN)?;
+        let new_value = FifoConfig {
+            accel: false,
+            gyro: false,
+        }
+        .apply_to_reg(original_value, self.model);
+        self.write_u8(registers::FIFO_EN, new_value)
+    }
+
+    pub fn set_fifo_mode(&mut self, mode: FifoMode) -> Result<(), Error<DI::Error>> {
+        if mode == FifoMode::StopOnFull {
+            self.model.require(Feature::FifoMode)?;
+        }
+        let original_value = self.read_u8(registers::CONFIG)?;
+        let new_value = match mode {
+            FifoMode::Stream => original_value & !registers::config::FIFO_MODE,
+            FifoMode::StopOnFull => original_value | registers::config::FIFO_MODE,
+        };
+        self.write_u8(registers::CONFIG, new_value)
+    }
+
+    pub fn get_fifo_mode(&mut self) -> Result<FifoMode, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::CONFIG)?;
+        if raw_value & registers::config::FIFO_MODE != 0 {
+            Ok(FifoMode::StopOnFull)
+        } else {
+            Ok(FifoMode::Stream)
+        }
+    }
+
+    /// Resets the FIFO, dropping all data stored in it.
+    ///
+    /// The bit clears itself, data is aligned to a packet boundary afterwards.
+    pub fn reset_fifo(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::USER_CTRL)?;
+        let new_value = original_value | registers::user_ctrl::FIFO_RST;
+        self.write_u8(registers::USER_CTRL, new_value)
+    }
+
+    /// Returns whether the FIFO overflowed since INT_STATUS was last read.
+    ///
+    /// Note that reading INT_STATUS clears all interrupt flags.
+    pub fn fifo_overflowed(&mut self) -> Result<bool, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::INT_STATUS)?;
+        Ok(raw_value & registers::int_status::FIFO_OFLOW_INT != 0)
+    }
+
+    /// Resets the FIFO if it overflowed, returns whether it did.
+    ///
+    /// In stream mode an overflow drops the oldest bytes, so the data left in
+    /// the FIFO no longer starts at a packet boundary. Resetting it drops the
+    /// data and realigns the following packets.
+    pub fn recover_fifo_overflow(&mut self) -> Result<bool, Error<DI::Error>> {
+        if self.fifo_overflowed()? {
+            self.reset_fifo()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Sets the FIFO watermark threshold in bytes, the max value is 1023.
+    ///
+    /// The watermark interrupt fires once the FIFO holds at least `threshold`
+    /// bytes. A threshold of 0 disables the watermark interrupt.
+    pub fn set_fifo_watermark(&mut self, threshold: u16) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::FifoWatermark)?;
+        let threshold = threshold.min(1023);
+        let original_value = self.read_u8(registers::FIFO_WM_TH1)?;
+        let new_value =
+            original_value & !registers::fifo_wm_th1::FIFO_WM_TH | (threshold >> 8) as u8;
+        self.write_u8(registers::FIFO_WM_TH1, new_value)?;
+        self.write_u8(registers::FIFO_WM_TH2, threshold as u8)
+    }
+
+    pub fn get_fifo_watermark(&mut self) -> Result<u16, Error<DI::Error>> {
+        self.model.require(Feature::FifoWatermark)?;
+        let value = self.read_u16(registers::FIFO_WM_TH1)?;
+        Ok(value & 0x03FF)
+    }
+
+    /// Returns whether the FIFO watermark has been reached.
+    ///
+    /// Reading the status clears the watermark interrupt.
+    pub fn fifo_watermark_reached(&mut self) -> Result<bool, Error<DI::Error>> {
+        self.model.require(Feature::FifoWatermark)?;
+        let raw_value = self.read_u8(registers::FIFO_WM_INT_STATUS)?;
+        Ok(raw_value & registers::fifo_wm_int_status::FIFO_WM_INT != 0)
+    }
+
+    /// Returns the number of bytes currently stored in the FIFO.
+    pub fn fifo_count(&mut self) -> Result<u16, Error<DI::Error>> {
+        // FIFO_COUNTH must be read first, both are read in one burst.
+        let value = self.read_u16(registers::FIFO_COUNTH)?;
+        Ok(value & 0x1FFF)
+    }
+
+    /// Returns an iterator decoding samples while draining the FIFO:
+    ///
+    /// ```rust,ignore
+    /// for sample in sensor.fifo_samples() {
+    ///     let sample = sample?;
+    /// }
+    /// ```
+    ///
+    /// The FIFO must hold whole packets, e.g. since the last FIFO reset.
+    pub fn fifo_samples(&mut self) -> FifoSamples<'_, DI> {
+        FifoSamples {
+            driver: self,
+            buf: [0; FIFO_CHUNK_SIZE],
+            pos: 0,
+            len: 0,
+            done: false,
+        }
+    }
+
+    /// Drains as many whole samples from the FIFO as are available and fit
+    /// in `out`, decoded with the current FIFO layout.
+    ///
+    /// Returns the number of samples written to `out`.
+    pub fn read_fifo_into(&mut self, out: &mut [FifoSample]) -> Result<usize, Error<DI::Error>> {
+        let config = self.fifo_config;
+        let size = config.packet_size();
+        if size == 0 {
+            return Ok(0);
+        }
+        let count = (self.fifo_count()? as usize / size).min(out.len());
+        let mut buf: [u8; FIFO_CHUNK_SIZE] = [0; FIFO_CHUNK_SIZE];
+        for samples in out[..count].chunks_mut(FIFO_CHUNK_SIZE / size) {
+            let data = &mut buf[..samples.len() * size];
+            self.read_buf(registers::FIFO_R_W, data)?;
+            for (sample, packet) in samples.iter_mut().zip(data.chunks_exact(size)) {
+                *sample = FifoSample::decode(packet, config);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Drains up to `N` whole samples from the FIFO into a vector.
+    #[cfg(feature = "heapless")]
+    pub fn drain_fifo<const N: usize>(
+        &mut self,
+    ) -> Result<heapless::Vec<FifoSample, N>, Error<DI::Error>> {
+        let mut samples = heapless::Vec::new();
+        // can't fail, the length is the capacity
+        let _ = samples.resize_default(N);
+        let count = self.read_fifo_into(&mut samples)?;
+        samples.truncate(count);
+        Ok(samples)
+    }
+
+    /// Reads as many bytes from the FIFO as are available and fit in `buf`.
+    ///
+    /// Returns the number of bytes read.
+    pub fn read_fifo(&mut self, buf: &mut [u8]) -> Result<usize, Error<DI::Error>> {
+        let count = self.fifo_count()? as usize;
+        let len = count.min(buf.len());
+        if len > 0 {
+            self.read_buf(registers::FIFO_R_W, &mut buf[..len])?;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::MPU6886_DEFAULT_ADDR;
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+    use std::vec::Vec;
+
+    /// Raw packets of the default layout, accel, temperature and gyro words
+    /// counting up from `first`.
+    fn packets(first: i16, count: usize) -> Vec<u8> {
+        (0..count as i16 * 7)
+            .flat_map(|word| (first + word).to_be_bytes())
+            .collect()
+    }
+
+    fn sample(first: i16) -> FifoSample {
+        FifoSample {
+            accel: Some((first, first + 1, first + 2)),
+            temp: Some(first + 3),
+            gyro: Some((first + 4, first + 5, first + 6)),
+        }
+    }
+
+    fn fifo_count(count: u16) -> Transaction {
+        Transaction::write_read(
+            MPU6886_DEFAULT_ADDR,
+            vec![registers::FIFO_COUNTH],
+            count.to_be_bytes().to_vec(),
+        )
+    }
+
+    fn fifo_read(data: Vec<u8>) -> Transaction {
+        Transaction::write_read(MPU6886_DEFAULT_ADDR, vec![registers::FIFO_R_W], data)
+    }
+
+    #[test]
+    fn parse_skips_partial_packet() {
+        let mut data = packets(0, 2);
+        data.extend_from_slice(&[1, 2, 3, 4, 5]);
+        let packets = FifoConfig::default().parse(&data);
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets.remainder(), &[1, 2, 3, 4, 5]);
+        let samples: Vec<_> = packets.collect();
+        assert_eq!(samples, [sample(0), sample(7)]);
+    }
+
+    #[test]
+    fn parse_single_source_layout() {
+        let config = FifoConfig {
+            accel: false,
+            gyro: true,
+        };
+        assert_eq!(config.packet_size(), 8);
+        let data = [0, 1, 0, 2, 0, 3, 0, 4, 9];
+        let mut packets = config.parse(&data);
+        assert_eq!(
+            packets.next(),
+            Some(FifoSample {
+                accel: None,
+                temp: Some(1),
+                gyro: Some((2, 3, 4)),
+            })
+        );
+        assert_eq!(packets.next(), None);
+        assert_eq!(packets.remainder(), &[9]);
+    }
+
+    #[test]
+    fn parse_without_sources_yields_nothing() {
+        let config = FifoConfig {
+            accel: false,
+            gyro: false,
+        };
+        let data = [1, 2, 3];
+        let mut packets = config.parse(&data);
+        assert_eq!(packets.len(), 0);
+        assert_eq!(packets.next(), None);
+        assert_eq!(packets.remainder(), &data);
+    }
+
+    #[test]
+    fn fifo_samples_refills_past_chunk_boundary() {
+        // 9 packets, 8 fit in a 112 byte chunk, the partial packet stays
+        let data = packets(0, 9);
+        let expectations = [
+            fifo_count(9 * 14 + 3),
+            fifo_read(data[..112].to_vec()),
+            fifo_count(14 + 3),
+            fifo_read(data[112..].to_vec()),
+            fifo_count(3),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        let samples: Vec<_> = sensor.fifo_samples().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), 9);
+        assert_eq!(samples[7], sample(49));
+        assert_eq!(samples[8], sample(56));
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn read_fifo_into_splits_reads_into_chunks() {
+        let data = packets(0, 9);
+        let expectations = [
+            fifo_count(9 * 14 + 3),
+            fifo_read(data[..112].to_vec()),
+            fifo_read(data[112..].to_vec()),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        let mut out = [FifoSample::default(); 10];
+        assert_eq!(sensor.read_fifo_into(&mut out).unwrap(), 9);
+        assert_eq!(out[8], sample(56));
+        assert_eq!(out[9], FifoSample::default());
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn read_fifo_into_stops_at_output_len() {
+        let data = packets(0, 2);
+        let expectations = [fifo_count(5 * 14), fifo_read(data)];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        let mut out = [FifoSample::default(); 2];
+        assert_eq!(sensor.read_fifo_into(&mut out).unwrap(), 2);
+        assert_eq!(out, [sample(0), sample(7)]);
+        sensor.destroy().done();
+    }
+}
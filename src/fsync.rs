@@ -0,0 +1,136 @@
+//! FSYNC input implementation.
+
+use crate::registers::{config, fsync_int, int_pin_cfg};
+use crate::{
+    registers, Error, FifoSample, Interface, InterruptLevel, Measurement, Mpu6886, RawMeasurement,
+};
+
+/// Where the FSYNC pin state is latched, its LSB replaces the LSB of that
+/// sample register (EXT_SYNC_SET).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtSync {
+    #[default]
+    Disabled,
+    TempOutL,
+    GyroXOutL,
+    GyroYOutL,
+    GyroZOutL,
+    AccelXOutL,
+    AccelYOutL,
+    AccelZOutL,
+}
+
+impl ExtSync {
+    /// Decodes EXT_SYNC_SET from a CONFIG register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        match (raw_value & config::EXT_SYNC_SET) >> 3 {
+            0 => ExtSync::Disabled,
+            1 => ExtSync::TempOutL,
+            2 => ExtSync::GyroXOutL,
+            3 => ExtSync::GyroYOutL,
+            4 => ExtSync::GyroZOutL,
+            5 => ExtSync::AccelXOutL,
+            6 => ExtSync::AccelYOutL,
+            _ => ExtSync::AccelZOutL,
+        }
+    }
+
+    /// Encodes EXT_SYNC_SET into a CONFIG register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        original_value & !config::EXT_SYNC_SET | (self as u8) << 3
+    }
+
+    /// Returns the FSYNC tag from the readings, `None` if tagging is disabled
+    /// or the tagged reading is missing.
+    fn tag(
+        self,
+        accel: Option<(i16, i16, i16)>,
+        gyro: Option<(i16, i16, i16)>,
+        temp: Option<i16>,
+    ) -> Option<bool> {
+        let value = match self {
+            ExtSync::Disabled => None,
+            ExtSync::TempOutL => temp,
+            ExtSync::GyroXOutL => gyro.map(|v| v.0),
+            ExtSync::GyroYOutL => gyro.map(|v| v.1),
+            ExtSync::GyroZOutL => gyro.map(|v| v.2),
+            ExtSync::AccelXOutL => accel.map(|v| v.0),
+            ExtSync::AccelYOutL => accel.map(|v| v.1),
+            ExtSync::AccelZOutL => accel.map(|v| v.2),
+        };
+        value.map(|value| value & 1 != 0)
+    }
+}
+
+impl RawMeasurement {
+    /// Returns whether FSYNC was seen for this sample, with the chip set to
+    /// `ext_sync`. `None` if tagging is disabled.
+    pub fn fsync_seen(&self, ext_sync: ExtSync) -> Option<bool> {
+        ext_sync.tag(Some(self.accel), Some(self.gyro), Some(self.temp))
+    }
+}
+
+impl FifoSample {
+    /// Returns whether FSYNC was seen for this sample, with the chip set to
+    /// `ext_sync`. `None` if tagging is disabled or the tagged reading isn't
+    /// in the FIFO.
+    ///
+    /// Scanning the drained samples finds the one a camera frame started on.
+    pub fn fsync_seen(&self, ext_sync: ExtSync) -> Option<bool> {
+        ext_sync.tag(self.accel, self.gyro, self.temp)
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Selects the sample register tagged with the FSYNC pin state.
+    pub fn set_ext_sync(&mut self, ext_sync: ExtSync) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::CONFIG)?;
+        let new_value = ext_sync.apply_to_reg(original_value);
+        self.write_u8(registers::CONFIG, new_value)
+    }
+
+    pub fn get_ext_sync(&mut self) -> Result<ExtSync, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::CONFIG)?;
+        Ok(ExtSync::from_reg(raw_value))
+    }
+
+    /// Makes the FSYNC pin an interrupt source, active at `level`.
+    ///
+    /// The flag shows up as [`InterruptStatus::FSYNC`](crate::InterruptStatus::FSYNC).
+    pub fn enable_fsync_interrupt(
+        &mut self,
+        level: InterruptLevel,
+    ) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::INT_PIN_CFG)?;
+        let mut new_value = original_value | int_pin_cfg::FSYNC_INT_MODE_EN;
+        match level {
+            InterruptLevel::ActiveHigh => new_value &= !int_pin_cfg::FSYNC_INT_LEVEL,
+            InterruptLevel::ActiveLow => new_value |= int_pin_cfg::FSYNC_INT_LEVEL,
+        }
+        self.write_u8(registers::INT_PIN_CFG, new_value)
+    }
+
+    pub fn disable_fsync_interrupt(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::INT_PIN_CFG)?;
+        let new_value = original_value & !int_pin_cfg::FSYNC_INT_MODE_EN;
+        self.write_u8(registers::INT_PIN_CFG, new_value)
+    }
+
+    /// Reads all sensors, with whether FSYNC was seen for this sample.
+    ///
+    /// The flag is `None` if tagging is disabled, see
+    /// [`Mpu6886::set_ext_sync`].
+    pub fn read_all_with_fsync(&mut self) -> Result<(Measurement, Option<bool>), Error<DI::Error>> {
+        let ext_sync = self.get_ext_sync()?;
+        let raw = self.read_all_raw()?;
+        Ok((self.scale_measurement(raw), raw.fsync_seen(ext_sync)))
+    }
+
+    /// Returns whether an FSYNC interrupt occurred, clearing the flag.
+    pub fn fsync_occurred(&mut self) -> Result<bool, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::FSYNC_INT)?;
+        Ok(raw_value & fsync_int::FSYNC_INT != 0)
+    }
+}
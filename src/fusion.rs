@@ -0,0 +1,276 @@
+//! Orientation estimation from accelerometer and gyroscope readings.
+
+use crate::Measurement;
+
+use libm::{asinf, atan2f, sqrtf};
+
+/// Orientation quaternion, rotating the sensor frame into the earth frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quaternion {
+    /// No rotation.
+    pub const IDENTITY: Self = Self {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    /// Returns (roll, pitch, yaw) in radians.
+    pub fn euler_angles(&self) -> (f32, f32, f32) {
+        let Self { w, x, y, z } = *self;
+        let roll = atan2f(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y));
+        let pitch = asinf((2.0 * (w * y - z * x)).clamp(-1.0, 1.0));
+        let yaw = atan2f(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z));
+        (roll, pitch, yaw)
+    }
+
+    fn normalized(self) -> Self {
+        let norm = sqrtf(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z);
+        if norm == 0.0 {
+            return Self::IDENTITY;
+        }
+        Self {
+            w: self.w / norm,
+            x: self.x / norm,
+            y: self.y / norm,
+            z: self.z / norm,
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Quaternion> for nalgebra::UnitQuaternion<f32> {
+    fn from(q: Quaternion) -> Self {
+        nalgebra::UnitQuaternion::new_normalize(nalgebra::Quaternion::new(q.w, q.x, q.y, q.z))
+    }
+}
+
+/// Scales a vector to unit length, `None` for a zero vector.
+fn normalize((x, y, z): (f32, f32, f32)) -> Option<(f32, f32, f32)> {
+    let norm = sqrtf(x * x + y * y + z * z);
+    if norm == 0.0 {
+        None
+    } else {
+        Some((x / norm, y / norm, z / norm))
+    }
+}
+
+/// Madgwick AHRS filter, IMU variant (no magnetometer).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Madgwick {
+    beta: f32,
+    sample_period: f32,
+    quaternion: Quaternion,
+}
+
+impl Madgwick {
+    /// Creates a filter with gain `beta`, updated at `sample_rate` Hz by
+    /// [`Madgwick::update_measurement`].
+    ///
+    /// A beta around 0.1 is a reasonable start.
+    pub fn new(beta: f32, sample_rate: f32) -> Self {
+        Self {
+            beta,
+            sample_period: 1.0 / sample_rate,
+            quaternion: Quaternion::IDENTITY,
+        }
+    }
+
+    pub fn set_beta(&mut self, beta: f32) {
+        self.beta = beta;
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_period = 1.0 / sample_rate;
+    }
+
+    pub fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    /// Restarts the estimation from no rotation.
+    pub fn reset(&mut self) {
+        self.quaternion = Quaternion::IDENTITY;
+    }
+
+    /// Feeds one sample taken at the configured sample rate.
+    pub fn update_measurement(&mut self, measurement: &Measurement) -> Quaternion {
+        self.update(measurement.gyro, measurement.accel, self.sample_period)
+    }
+
+    /// Feeds one sample, `gyro` in rad/s, `accel` in any unit, `dt` in
+    /// seconds since the previous one.
+    pub fn update(
+        &mut self,
+        (gx, gy, gz): (f32, f32, f32),
+        accel: (f32, f32, f32),
+        dt: f32,
+    ) -> Quaternion {
+        let Quaternion {
+            w: q0,
+            x: q1,
+            y: q2,
+            z: q3,
+        } = self.quaternion;
+
+        // rate of change from the gyroscope
+        let mut q_dot0 = 0.5 * (-q1 * gx - q2 * gy - q3 * gz);
+        let mut q_dot1 = 0.5 * (q0 * gx + q2 * gz - q3 * gy);
+        let mut q_dot2 = 0.5 * (q0 * gy - q1 * gz + q3 * gx);
+        let mut q_dot3 = 0.5 * (q0 * gz + q1 * gy - q2 * gx);
+
+        // gradient descent correction towards the measured gravity
+        if let Some((ax, ay, az)) = normalize(accel) {
+            let s0 = 4.0 * q0 * q2 * q2 + 2.0 * q2 * ax + 4.0 * q0 * q1 * q1 - 2.0 * q1 * ay;
+            let s1 =
+                4.0 * q1 * q3 * q3 - 2.0 * q3 * ax + 4.0 * q0 * q0 * q1 - 2.0 * q0 * ay - 4.0 * q1
+                    + 8.0 * q1 * q1 * q1
+                    + 8.0 * q1 * q2 * q2
+                    + 4.0 * q1 * az;
+            let s2 =
+                4.0 * q0 * q0 * q2 + 2.0 * q0 * ax + 4.0 * q2 * q3 * q3 - 2.0 * q3 * ay - 4.0 * q2
+                    + 8.0 * q2 * q1 * q1
+                    + 8.0 * q2 * q2 * q2
+                    + 4.0 * q2 * az;
+            let s3 = 4.0 * q1 * q1 * q3 - 2.0 * q1 * ax + 4.0 * q2 * q2 * q3 - 2.0 * q2 * ay;
+            let norm = sqrtf(s0 * s0 + s1 * s1 + s2 * s2 + s3 * s3);
+            if norm != 0.0 {
+                q_dot0 -= self.beta * s0 / norm;
+                q_dot1 -= self.beta * s1 / norm;
+                q_dot2 -= self.beta * s2 / norm;
+                q_dot3 -= self.beta * s3 / norm;
+            }
+        }
+
+        self.quaternion = Quaternion {
+            w: q0 + q_dot0 * dt,
+            x: q1 + q_dot1 * dt,
+            y: q2 + q_dot2 * dt,
+            z: q3 + q_dot3 * dt,
+        }
+        .normalized();
+        self.quaternion
+    }
+}
+
+/// Mahony complementary filter, IMU variant (no magnetometer).
+///
+/// Corrects the gyroscope with a PI controller on the gravity direction
+/// error, which converges smoothly for slow motion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mahony {
+    kp: f32,
+    ki: f32,
+    sample_period: f32,
+    integral: (f32, f32, f32),
+    quaternion: Quaternion,
+}
+
+impl Mahony {
+    /// Creates a filter with proportional gain `kp` and integral gain `ki`,
+    /// updated at `sample_rate` Hz by [`Mahony::update_measurement`].
+    ///
+    /// `kp` of 1.0 and `ki` of 0.0 are a reasonable start.
+    pub fn new(kp: f32, ki: f32, sample_rate: f32) -> Self {
+        Self {
+            kp,
+            ki,
+            sample_period: 1.0 / sample_rate,
+            integral: (0.0, 0.0, 0.0),
+            quaternion: Quaternion::IDENTITY,
+        }
+    }
+
+    pub fn set_gains(&mut self, kp: f32, ki: f32) {
+        self.kp = kp;
+        self.ki = ki;
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_period = 1.0 / sample_rate;
+    }
+
+    pub fn quaternion(&self) -> Quaternion {
+        self.quaternion
+    }
+
+    /// Restarts the estimation from no rotation, clearing the integral term.
+    pub fn reset(&mut self) {
+        self.integral = (0.0, 0.0, 0.0);
+        self.quaternion = Quaternion::IDENTITY;
+    }
+
+    /// Feeds one sample taken at the configured sample rate.
+    pub fn update_measurement(&mut self, measurement: &Measurement) -> Quaternion {
+        self.update(measurement.gyro, measurement.accel, self.sample_period)
+    }
+
+    /// Feeds one sample, `gyro` in rad/s, `accel` in any unit, `dt` in
+    /// seconds since the previous one.
+    pub fn update(
+        &mut self,
+        (mut gx, mut gy, mut gz): (f32, f32, f32),
+        accel: (f32, f32, f32),
+        dt: f32,
+    ) -> Quaternion {
+        let Quaternion {
+            w: q0,
+            x: q1,
+            y: q2,
+            z: q3,
+        } = self.quaternion;
+
+        if let Some((ax, ay, az)) = normalize(accel) {
+            // half of the estimated gravity direction
+            let vx = q1 * q3 - q0 * q2;
+            let vy = q0 * q1 + q2 * q3;
+            let vz = q0 * q0 - 0.5 + q3 * q3;
+            // half of the error, cross product of measured and estimated
+            let ex = ay * vz - az * vy;
+            let ey = az * vx - ax * vz;
+            let ez = ax * vy - ay * vx;
+
+            if self.ki > 0.0 {
+                let (ix, iy, iz) = &mut self.integral;
+                *ix += 2.0 * self.ki * ex * dt;
+                *iy += 2.0 * self.ki * ey * dt;
+                *iz += 2.0 * self.ki * ez * dt;
+                gx += *ix;
+                gy += *iy;
+                gz += *iz;
+            } else {
+                self.integral = (0.0, 0.0, 0.0);
+            }
+
+            gx += 2.0 * self.kp * ex;
+            gy += 2.0 * self.kp * ey;
+            gz += 2.0 * self.kp * ez;
+        }
+
+        let (gx, gy, gz) = (gx * 0.5 * dt, gy * 0.5 * dt, gz * 0.5 * dt);
+        self.quaternion = Quaternion {
+            w: q0 - q1 * gx - q2 * gy - q3 * gz,
+            x: q1 + q0 * gx + q2 * gz - q3 * gy,
+            y: q2 + q0 * gy - q1 * gz + q3 * gx,
+            z: q3 + q0 * gz + q1 * gy - q2 * gx,
+        }
+        .normalized();
+        self.quaternion
+    }
+}
@@ -1,9 +1,23 @@
 //! Gyroscope implementation.
 
-use crate::{Error, I2c, Mpu6886};
+use crate::model::Feature;
+use crate::registers::{config, gyro_config, lp_mode_cfg, pwr_mgmt_1};
+use crate::{registers, xyz_from_be_bytes, Error, Interface, Mpu6886};
 use core::f32::consts::PI;
 
+/// Gyroscope sensitivity at ±250 dps, in LSB per dps.
+pub const GYRO_SENSITIVITY_250DPS: f32 = 131.0;
+/// Gyroscope sensitivity at ±500 dps, in LSB per dps.
+pub const GYRO_SENSITIVITY_500DPS: f32 = 65.5;
+/// Gyroscope sensitivity at ±1000 dps, in LSB per dps.
+pub const GYRO_SENSITIVITY_1000DPS: f32 = 32.8;
+/// Gyroscope sensitivity at ±2000 dps, in LSB per dps.
+pub const GYRO_SENSITIVITY_2000DPS: f32 = 16.4;
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GyroScaleRange {
     Range250Dps,
     Range500Dps,
@@ -11,77 +25,364 @@ pub enum GyroScaleRange {
     Range2000Dps,
 }
 
-impl<I2C: I2c> Mpu6886<I2C> {
-    pub fn get_gyro_scale_range(&mut self) -> Result<GyroScaleRange, Error> {
-        let raw_value = self.read_u8(0x1B)?;
-        let selection = (raw_value & 0b00011000) >> 3;
+/// Gyroscope DLPF bandwidth, set through DLPF_CFG and FCHOICE_B.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GyroBandwidth {
+    /// 250 Hz, 8 kHz internal rate.
+    Hz250,
+    /// 176 Hz, 1 kHz internal rate.
+    Hz176,
+    /// 92 Hz, 1 kHz internal rate.
+    Hz92,
+    /// 41 Hz, 1 kHz internal rate.
+    Hz41,
+    /// 20 Hz, 1 kHz internal rate.
+    Hz20,
+    /// 10 Hz, 1 kHz internal rate.
+    Hz10,
+    /// 5 Hz, 1 kHz internal rate.
+    Hz5,
+    /// 3281 Hz, 8 kHz internal rate.
+    Hz3281,
+    /// 3281 Hz with the DLPF bypassed, 32 kHz internal rate.
+    Hz3281At32kHz,
+    /// 8173 Hz with the DLPF bypassed, 32 kHz internal rate.
+    Hz8173,
+}
+
+impl GyroBandwidth {
+    /// Decodes the bandwidth from CONFIG and GYRO_CONFIG register values.
+    pub(crate) fn from_regs(config: u8, gyro_config: u8) -> Self {
+        match gyro_config & registers::gyro_config::FCHOICE_B {
+            0b00 => match config & registers::config::DLPF_CFG {
+                0 => GyroBandwidth::Hz250,
+                1 => GyroBandwidth::Hz176,
+                2 => GyroBandwidth::Hz92,
+                3 => GyroBandwidth::Hz41,
+                4 => GyroBandwidth::Hz20,
+                5 => GyroBandwidth::Hz10,
+                6 => GyroBandwidth::Hz5,
+                _ => GyroBandwidth::Hz3281,
+            },
+            0b10 => GyroBandwidth::Hz3281At32kHz,
+            _ => GyroBandwidth::Hz8173,
+        }
+    }
+
+    /// Returns the DLPF_CFG and FCHOICE_B values.
+    pub(crate) fn to_fields(self) -> (u8, u8) {
+        match self {
+            GyroBandwidth::Hz250 => (0, 0b00),
+            GyroBandwidth::Hz176 => (1, 0b00),
+            GyroBandwidth::Hz92 => (2, 0b00),
+            GyroBandwidth::Hz41 => (3, 0b00),
+            GyroBandwidth::Hz20 => (4, 0b00),
+            GyroBandwidth::Hz10 => (5, 0b00),
+            GyroBandwidth::Hz5 => (6, 0b00),
+            GyroBandwidth::Hz3281 => (7, 0b00),
+            GyroBandwidth::Hz3281At32kHz => (0, 0b10),
+            GyroBandwidth::Hz8173 => (0, 0b01),
+        }
+    }
+}
+
+/// Gyroscope offsets, removed by the chip from every reading.
+///
+/// One LSB is 1/32.8 dps (the ±1000 dps resolution), independent of the scale
+/// range.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GyroOffsets {
+    pub x: i16,
+    pub y: i16,
+    pub z: i16,
+}
+
+/// Averaging filter used in gyroscope low power mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GyroAveraging {
+    Avg1,
+    Avg2,
+    Avg4,
+    Avg8,
+    Avg16,
+    Avg32,
+    Avg64,
+    Avg128,
+}
+
+impl GyroAveraging {
+    /// Decodes the averaging from a LP_MODE_CFG register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        let selection = (raw_value & lp_mode_cfg::G_AVGCFG) >> 4;
         match selection {
+            0 => GyroAveraging::Avg1,
+            1 => GyroAveraging::Avg2,
+            2 => GyroAveraging::Avg4,
+            3 => GyroAveraging::Avg8,
+            4 => GyroAveraging::Avg16,
+            5 => GyroAveraging::Avg32,
+            6 => GyroAveraging::Avg64,
+            _ => GyroAveraging::Avg128,
+        }
+    }
+}
+
+impl GyroScaleRange {
+    /// Decodes the range from a GYRO_CONFIG register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        let selection = (raw_value & gyro_config::FS_SEL) >> 3;
+        match selection {
+            0 => GyroScaleRange::Range250Dps,
+            1 => GyroScaleRange::Range500Dps,
+            2 => GyroScaleRange::Range1000Dps,
+            _ => GyroScaleRange::Range2000Dps,
+        }
+    }
+
+    /// Encodes the range into a GYRO_CONFIG register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        original_value & !gyro_config::FS_SEL | (self as u8) << 3
+    }
+
+    /// Returns the sensitivity, in LSB per dps.
+    pub fn lsb_per_dps(self) -> f32 {
+        match self {
+            GyroScaleRange::Range250Dps => GYRO_SENSITIVITY_250DPS,
+            GyroScaleRange::Range500Dps => GYRO_SENSITIVITY_500DPS,
+            GyroScaleRange::Range1000Dps => GYRO_SENSITIVITY_1000DPS,
+            GyroScaleRange::Range2000Dps => GYRO_SENSITIVITY_2000DPS,
+        }
+    }
+
+    /// Returns the sensitivity in LSB per 10 dps, rounded, for integer-only
+    /// conversions.
+    pub const fn lsb_per_10_dps(self) -> i32 {
+        match self {
+            GyroScaleRange::Range250Dps => 1310,
+            GyroScaleRange::Range500Dps => 655,
+            GyroScaleRange::Range1000Dps => 328,
+            GyroScaleRange::Range2000Dps => 164,
+        }
+    }
+
+    /// Returns the full scale value, in dps.
+    pub fn max_value(self) -> f32 {
+        match self {
+            GyroScaleRange::Range250Dps => 250.0,
+            GyroScaleRange::Range500Dps => 500.0,
+            GyroScaleRange::Range1000Dps => 1000.0,
+            GyroScaleRange::Range2000Dps => 2000.0,
+        }
+    }
+}
+
+impl core::fmt::Display for GyroScaleRange {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "±{} dps", self.max_value())
+    }
+}
+
+/// Converts from the GYRO_FS_SEL field value, returning the value back if it
+/// is out of range.
+impl TryFrom<u8> for GyroScaleRange {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
             0 => Ok(GyroScaleRange::Range250Dps),
             1 => Ok(GyroScaleRange::Range500Dps),
             2 => Ok(GyroScaleRange::Range1000Dps),
-            _ => Ok(GyroScaleRange::Range2000Dps),
+            3 => Ok(GyroScaleRange::Range2000Dps),
+            _ => Err(value),
         }
     }
+}
+
+/// Converts into the GYRO_FS_SEL field value.
+impl From<GyroScaleRange> for u8 {
+    fn from(value: GyroScaleRange) -> Self {
+        value as u8
+    }
+}
+
+/// Converts raw gyroscope readings with the given range.
+pub(crate) fn scale_gyro(raw: (i16, i16, i16), range: GyroScaleRange) -> (f32, f32, f32) {
+    let factor = range.lsb_per_dps();
+    (
+        raw.0 as f32 / factor * PI / 180.0,
+        raw.1 as f32 / factor * PI / 180.0,
+        raw.2 as f32 / factor * PI / 180.0,
+    )
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Returns the scale range cached by the driver, without bus traffic.
+    pub fn gyro_scale_range(&self) -> GyroScaleRange {
+        self.gyro_range
+    }
+
+    pub fn get_gyro_scale_range(&mut self) -> Result<GyroScaleRange, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::GYRO_CONFIG)?;
+        Ok(GyroScaleRange::from_reg(raw_value))
+    }
 
-    pub fn set_gyro_scale_range(&mut self, value: GyroScaleRange) -> Result<(), Error> {
-        let original_value = self.read_u8(0x1B)?;
-        let choice_value = value as u8;
-        let reg_value = (original_value & 0b11100111) | choice_value << 3;
-        self.write_u8(0x1B, reg_value)?;
+    pub fn set_gyro_scale_range(&mut self, value: GyroScaleRange) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::GYRO_CONFIG)?;
+        let reg_value = value.apply_to_reg(original_value);
+        self.write_u8(registers::GYRO_CONFIG, reg_value)?;
         self.gyro_range = value;
         Ok(())
     }
 
-    pub fn gyro_standby(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value | 0b00010000;
-        self.write_u8(0x6B, new_value)
+    /// Sets the gyroscope (and temperature sensor) DLPF bandwidth.
+    ///
+    /// This also changes the internal sample rate, see
+    /// [`Mpu6886::output_data_rate`].
+    pub fn set_gyro_bandwidth(&mut self, bandwidth: GyroBandwidth) -> Result<(), Error<DI::Error>> {
+        let (dlpf_cfg, fchoice_b) = bandwidth.to_fields();
+        if fchoice_b != 0 {
+            self.model.require(Feature::GyroDlpfBypass)?;
+        }
+        let original_value = self.read_u8(registers::CONFIG)?;
+        let new_value = original_value & !config::DLPF_CFG | dlpf_cfg;
+        self.write_u8(registers::CONFIG, new_value)?;
+        let original_value = self.read_u8(registers::GYRO_CONFIG)?;
+        let new_value = original_value & !gyro_config::FCHOICE_B | fchoice_b;
+        self.write_u8(registers::GYRO_CONFIG, new_value)
+    }
+
+    pub fn get_gyro_bandwidth(&mut self) -> Result<GyroBandwidth, Error<DI::Error>> {
+        // CONFIG, GYRO_CONFIG
+        let mut buf: [u8; 2] = [0; 2];
+        self.read_buf(registers::CONFIG, &mut buf)?;
+        Ok(GyroBandwidth::from_regs(buf[0], buf[1]))
+    }
+
+    /// Writes the XG/YG/ZG_OFFS_USR registers.
+    pub fn set_gyro_offsets(&mut self, offsets: GyroOffsets) -> Result<(), Error<DI::Error>> {
+        let [x_h, x_l] = offsets.x.to_be_bytes();
+        let [y_h, y_l] = offsets.y.to_be_bytes();
+        let [z_h, z_l] = offsets.z.to_be_bytes();
+        for (offset, value) in [x_h, x_l, y_h, y_l, z_h, z_l].iter().enumerate() {
+            self.write_u8(registers::XG_OFFS_USRH + offset as u8, *value)?;
+        }
+        Ok(())
     }
 
-    pub fn gyro_activate(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value & 0b11101111;
-        self.write_u8(0x6B, new_value)
+    /// Reads the XG/YG/ZG_OFFS_USR registers.
+    pub fn get_gyro_offsets(&mut self) -> Result<GyroOffsets, Error<DI::Error>> {
+        let mut xyz_buf: [u8; 6] = [0; 6];
+        self.read_buf(registers::XG_OFFS_USRH, &mut xyz_buf)?;
+        let (x, y, z) = xyz_from_be_bytes(&xyz_buf);
+        Ok(GyroOffsets { x, y, z })
     }
 
-    pub fn turn_off_gyro(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6C)?;
-        let new_value = original_value | 0b00000111;
-        self.write_u8(0x6C, new_value)
+    #[deprecated(note = "use `set_power_mode(PowerMode::Standby)`")]
+    pub fn gyro_standby(&mut self) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::GyroStandby)?;
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value | pwr_mgmt_1::GYRO_STANDBY;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
     }
 
-    pub fn turn_on_gyro(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6C)?;
-        let new_value = original_value & 0b11111000;
-        self.write_u8(0x6C, new_value)
+    #[deprecated(note = "use `set_power_mode`")]
+    pub fn gyro_activate(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value & !pwr_mgmt_1::GYRO_STANDBY;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
+    }
+
+    /// Runs the gyroscope in low power mode, averaging the given number of
+    /// samples per output.
+    ///
+    /// Lowers current for coarse rotation detection at the expense of noise.
+    pub fn enable_gyro_low_power_mode(
+        &mut self,
+        averaging: GyroAveraging,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::GyroLowPower)?;
+        let original_value = self.read_u8(registers::LP_MODE_CFG)?;
+        let new_value = original_value & !(lp_mode_cfg::GYRO_CYCLE | lp_mode_cfg::G_AVGCFG)
+            | lp_mode_cfg::GYRO_CYCLE
+            | (averaging as u8) << 4;
+        self.write_u8(registers::LP_MODE_CFG, new_value)
+    }
+
+    pub fn disable_gyro_low_power_mode(&mut self) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::GyroLowPower)?;
+        let original_value = self.read_u8(registers::LP_MODE_CFG)?;
+        let new_value = original_value & !lp_mode_cfg::GYRO_CYCLE;
+        self.write_u8(registers::LP_MODE_CFG, new_value)
+    }
+
+    /// Returns whether the gyroscope runs in low power mode and its averaging.
+    pub fn get_gyro_low_power_mode(&mut self) -> Result<(bool, GyroAveraging), Error<DI::Error>> {
+        self.model.require(Feature::GyroLowPower)?;
+        let raw_value = self.read_u8(registers::LP_MODE_CFG)?;
+        Ok((
+            raw_value & lp_mode_cfg::GYRO_CYCLE != 0,
+            GyroAveraging::from_reg(raw_value),
+        ))
     }
 
     /// Returns measured angular acceleration, (X, Y, Z), in rad/s
-    pub fn gyro(&mut self) -> Result<(f32, f32, f32), Error> {
-        const SENSITIVITY: f32 = 131.0; // degree per second
-        let mut xyz_buf: [u8; 6] = [0; 6];
-        self.read_buf(0x43, &mut xyz_buf)?;
-        let x_raw = (xyz_buf[0] as u16) << 8 | (xyz_buf[1] as u16);
-        let y_raw = (xyz_buf[2] as u16) << 8 | (xyz_buf[3] as u16);
-        let z_raw = (xyz_buf[4] as u16) << 8 | (xyz_buf[5] as u16);
-        let factor = match self.gyro_range {
-            GyroScaleRange::Range250Dps => SENSITIVITY,
-            GyroScaleRange::Range500Dps => SENSITIVITY / 2.0,
-            GyroScaleRange::Range1000Dps => SENSITIVITY / 4.0,
-            GyroScaleRange::Range2000Dps => SENSITIVITY / 8.0,
-        };
-        let x_real = x_raw as i16 as f32 / factor * PI / 180.0;
-        let y_real = y_raw as i16 as f32 / factor * PI / 180.0;
-        let z_real = z_raw as i16 as f32 / factor * PI / 180.0;
-        Ok((x_real, y_real, z_real))
-    }
-
-    pub fn gyro_raw(&mut self) -> Result<(i16, i16, i16), Error> {
+    pub fn gyro(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        let raw = self.gyro_raw()?;
+        Ok(self.conversion().gyro(raw))
+    }
+
+    /// Same as [`Mpu6886::gyro`], in rad/s.
+    pub fn gyro_rads(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        self.gyro()
+    }
+
+    /// Returns measured angular rate, (X, Y, Z), in °/s.
+    pub fn gyro_dps(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        let (x, y, z) = self.gyro()?;
+        Ok((x.to_degrees(), y.to_degrees(), z.to_degrees()))
+    }
+
+    /// Returns measured angular rate, (X, Y, Z), in mdps (millidegree per
+    /// second), using integer math only.
+    ///
+    /// For targets without FPU. The software calibration isn't applied.
+    pub fn gyro_mdps(&mut self) -> Result<(i32, i32, i32), Error<DI::Error>> {
+        let (x, y, z) = self.gyro_raw()?;
+        let sensitivity = self.gyro_range.lsb_per_10_dps();
+        let mdps = |raw: i16| raw as i32 * 10000 / sensitivity;
+        Ok(self.mounting.apply_fixed((mdps(x), mdps(y), mdps(z))))
+    }
+
+    /// Returns raw gyroscope readings, (X, Y, Z), in LSB of the current
+    /// scale range.
+    pub fn gyro_raw(&mut self) -> Result<(i16, i16, i16), Error<DI::Error>> {
+        self.check_ready()?;
         let mut xyz_buf: [u8; 6] = [0; 6];
-        self.read_buf(0x43, &mut xyz_buf)?;
-        let x_raw = (xyz_buf[0] as u16) << 8 | (xyz_buf[1] as u16);
-        let y_raw = (xyz_buf[2] as u16) << 8 | (xyz_buf[3] as u16);
-        let z_raw = (xyz_buf[4] as u16) << 8 | (xyz_buf[5] as u16);
-        Ok((x_raw as i16, y_raw as i16, z_raw as i16))
+        self.read_buf(registers::GYRO_XOUT_H, &mut xyz_buf)?;
+        Ok(xyz_from_be_bytes(&xyz_buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_sensitivity_matches_constants() {
+        for range in [
+            GyroScaleRange::Range250Dps,
+            GyroScaleRange::Range500Dps,
+            GyroScaleRange::Range1000Dps,
+            GyroScaleRange::Range2000Dps,
+        ] {
+            let sensitivity = range.lsb_per_10_dps() as f32 / 10.0;
+            assert!((sensitivity - range.lsb_per_dps()).abs() < 0.05);
+        }
     }
 }
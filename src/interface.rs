@@ -0,0 +1,220 @@
+//! Bus interfaces.
+//!
+//! The MPU6886 talks I2C or SPI, the driver accesses its registers through
+//! [`Interface`] so the rest of the code doesn't care which.
+
+use crate::{BusOperation, Error};
+
+use core::fmt::Debug;
+
+use embedded_hal::i2c::{I2c, Operation as I2cOperation};
+use embedded_hal::spi::{Operation, SpiDevice};
+
+/// Bit 7 of the SPI register address, set for reads.
+const SPI_READ: u8 = 0b10000000;
+
+/// Register access over a bus.
+///
+/// Bus errors are reported as [`Error::Transfer`], with the register access
+/// that failed.
+pub trait Interface {
+    /// Underlying bus error.
+    type Error: Debug;
+
+    /// Writes one register.
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<Self::Error>>;
+
+    /// Reads consecutive registers starting at `reg`.
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<Self::Error>>;
+
+    /// Returns the chip's bus address, `None` on buses without one.
+    fn address(&self) -> Option<u8> {
+        None
+    }
+
+    /// Writes consecutive registers starting at `reg`.
+    ///
+    /// The default writes them one by one, buses should use a single
+    /// auto-incrementing transaction.
+    fn write_registers(&mut self, reg: u8, data: &[u8]) -> Result<(), Error<Self::Error>> {
+        for (offset, value) in data.iter().enumerate() {
+            self.write_register(reg + offset as u8, *value)?;
+        }
+        Ok(())
+    }
+}
+
+/// I2C bus and chip address.
+#[derive(Debug)]
+pub struct I2cInterface<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C> I2cInterface<I2C> {
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self { i2c, address }
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C: I2c> Interface for I2cInterface<I2C> {
+    type Error = I2C::Error;
+
+    fn address(&self) -> Option<u8> {
+        Some(self.address)
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<Self::Error>> {
+        self.i2c
+            .write(self.address, &[reg, value])
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            })
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<Self::Error>> {
+        self.i2c
+            .write_read(self.address, &[reg], buf)
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::ReadReg(reg),
+                error,
+            })
+    }
+
+    fn write_registers(&mut self, reg: u8, data: &[u8]) -> Result<(), Error<Self::Error>> {
+        // adjacent writes go out without a restart
+        let mut operations = [I2cOperation::Write(&[reg]), I2cOperation::Write(data)];
+        self.i2c
+            .transaction(self.address, &mut operations)
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            })
+    }
+}
+
+/// SPI device, with the chip select handled by the [`SpiDevice`].
+///
+/// The chip uses SPI mode 0 or 3.
+#[derive(Debug)]
+pub struct SpiInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> SpiInterface<SPI> {
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI: SpiDevice> Interface for SpiInterface<SPI> {
+    type Error = SPI::Error;
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<Self::Error>> {
+        self.spi
+            .write(&[reg & !SPI_READ, value])
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            })
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<Self::Error>> {
+        self.spi
+            .transaction(&mut [Operation::Write(&[reg | SPI_READ]), Operation::Read(buf)])
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::ReadReg(reg),
+                error,
+            })
+    }
+
+    fn write_registers(&mut self, reg: u8, data: &[u8]) -> Result<(), Error<Self::Error>> {
+        self.spi
+            .transaction(&mut [Operation::Write(&[reg & !SPI_READ]), Operation::Write(data)])
+            .map_err(|error| Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            })
+    }
+}
+
+/// Interface wrapper retrying failed transactions, for noisy shared buses.
+///
+/// `retryable` decides which errors are worth another attempt, e.g.
+/// [`Error::is_transient`] for I2C:
+///
+/// ```rust,ignore
+/// let iface = Retry::new(I2cInterface::new(i2c, MPU6886_DEFAULT_ADDR), 3, Error::is_transient);
+/// let mut sensor = Mpu6886::new_with_interface(iface, Model::Mpu6886);
+/// ```
+pub struct Retry<DI: Interface> {
+    iface: DI,
+    max_attempts: u8,
+    retryable: fn(&Error<DI::Error>) -> bool,
+}
+
+impl<DI: Interface + Debug> Debug for Retry<DI> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Retry")
+            .field("iface", &self.iface)
+            .field("max_attempts", &self.max_attempts)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<DI: Interface> Retry<DI> {
+    /// Wraps `iface`, making up to `max_attempts` attempts per transaction.
+    pub fn new(iface: DI, max_attempts: u8, retryable: fn(&Error<DI::Error>) -> bool) -> Self {
+        Self {
+            iface,
+            max_attempts: max_attempts.max(1),
+            retryable,
+        }
+    }
+
+    pub fn release(self) -> DI {
+        self.iface
+    }
+
+    fn attempt<T>(
+        &mut self,
+        mut transaction: impl FnMut(&mut DI) -> Result<T, Error<DI::Error>>,
+    ) -> Result<T, Error<DI::Error>> {
+        let mut attempts = 1;
+        loop {
+            match transaction(&mut self.iface) {
+                Err(e) if attempts < self.max_attempts && (self.retryable)(&e) => attempts += 1,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<DI: Interface> Interface for Retry<DI> {
+    type Error = DI::Error;
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<Self::Error>> {
+        self.attempt(|iface| iface.write_register(reg, value))
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<Self::Error>> {
+        self.attempt(|iface| iface.read_registers(reg, buf))
+    }
+
+    fn write_registers(&mut self, reg: u8, data: &[u8]) -> Result<(), Error<Self::Error>> {
+        self.attempt(|iface| iface.write_registers(reg, data))
+    }
+
+    fn address(&self) -> Option<u8> {
+        self.iface.address()
+    }
+}
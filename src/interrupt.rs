@@ -0,0 +1,448 @@
+//! Interrupt implementation.
+
+use crate::model::Feature;
+use crate::registers::{int_enable, int_pin_cfg, int_status};
+use crate::{registers, Error, Interface, Measurement, Model, Mpu6886};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::InputPin;
+
+bitflags::bitflags! {
+    /// Interrupt flags, decoded from INT_STATUS, FIFO_WM_INT_STATUS and
+    /// FSYNC_INT.
+    ///
+    /// The low byte follows the INT_STATUS layout, `FSYNC` and
+    /// `FIFO_WATERMARK` live in the high byte as they come from other
+    /// registers. Chips with a single wake-on-motion flag set all three
+    /// `WOM_*` flags.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct InterruptStatus: u16 {
+        const WOM_X = int_status::WOM_X_INT as u16;
+        const WOM_Y = int_status::WOM_Y_INT as u16;
+        const WOM_Z = int_status::WOM_Z_INT as u16;
+        const FIFO_OVERFLOW = int_status::FIFO_OFLOW_INT as u16;
+        const GDRIVE = int_status::GDRIVE_INT as u16;
+        const DATA_READY = int_status::DATA_RDY_INT as u16;
+        const FSYNC = 0b1_00000000;
+        const FIFO_WATERMARK = 0b10_00000000;
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InterruptStatus {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "InterruptStatus({=u16:#b})", self.bits())
+    }
+}
+
+impl InterruptStatus {
+    /// Decodes the flags of an INT_STATUS register value of `model`.
+    pub(crate) fn from_int_status(raw_value: u8, model: Model) -> Self {
+        let mut status = Self::from_bits_truncate(
+            (raw_value
+                & (int_status::FIFO_OFLOW_INT | int_status::GDRIVE_INT | int_status::DATA_RDY_INT))
+                as u16,
+        );
+        let wom = raw_value & model.wom_int_bits();
+        if model.supports(Feature::PerAxisWakeOnMotion) {
+            status |= Self::from_bits_truncate(wom as u16);
+        } else if wom != 0 {
+            status |= Self::WOM_X | Self::WOM_Y | Self::WOM_Z;
+        }
+        status
+    }
+
+    /// Returns whether any wake-on-motion flag is set.
+    pub fn wake_on_motion(&self) -> bool {
+        self.intersects(Self::WOM_X | Self::WOM_Y | Self::WOM_Z)
+    }
+}
+
+bitflags::bitflags! {
+    /// Interrupt sources driving the INT pin, see
+    /// [`Mpu6886::set_interrupt_sources`].
+    ///
+    /// The low byte follows the INT_ENABLE layout, `FSYNC` and
+    /// `FIFO_WATERMARK` live in the high byte as they are enabled elsewhere.
+    /// Chips with a single wake-on-motion enable only take `WOM` as a whole.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct InterruptSources: u16 {
+        const WOM_X = int_enable::WOM_X_INT_EN as u16;
+        const WOM_Y = int_enable::WOM_Y_INT_EN as u16;
+        const WOM_Z = int_enable::WOM_Z_INT_EN as u16;
+        const FIFO_OVERFLOW = int_enable::FIFO_OFLOW_EN as u16;
+        const DATA_READY = int_enable::DATA_RDY_INT_EN as u16;
+        const FSYNC = 0b1_00000000;
+        const FIFO_WATERMARK = 0b10_00000000;
+        const WOM = Self::WOM_X.bits() | Self::WOM_Y.bits() | Self::WOM_Z.bits();
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for InterruptSources {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "InterruptSources({=u16:#b})", self.bits())
+    }
+}
+
+/// Active level of the INT pin.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterruptLevel {
+    #[default]
+    ActiveHigh,
+    ActiveLow,
+}
+
+/// Output driver of the INT pin.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterruptDrive {
+    #[default]
+    PushPull,
+    OpenDrain,
+}
+
+/// How long the INT pin stays asserted.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterruptLatch {
+    /// Emits a 50 µs pulse.
+    #[default]
+    Pulse,
+    /// Held until the interrupt is cleared.
+    Latched,
+}
+
+/// INT pin hardware configuration, the default matches the chip's reset state.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterruptPinConfig {
+    pub level: InterruptLevel,
+    pub drive: InterruptDrive,
+    pub latch: InterruptLatch,
+    /// Clears the interrupt on any register read instead of only on reading
+    /// INT_STATUS.
+    pub clear_on_any_read: bool,
+}
+
+impl InterruptPinConfig {
+    /// Decodes the config from an INT_PIN_CFG register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        Self {
+            level: if raw_value & int_pin_cfg::INT_LEVEL != 0 {
+                InterruptLevel::ActiveLow
+            } else {
+                InterruptLevel::ActiveHigh
+            },
+            drive: if raw_value & int_pin_cfg::INT_OPEN != 0 {
+                InterruptDrive::OpenDrain
+            } else {
+                InterruptDrive::PushPull
+            },
+            latch: if raw_value & int_pin_cfg::LATCH_INT_EN != 0 {
+                InterruptLatch::Latched
+            } else {
+                InterruptLatch::Pulse
+            },
+            clear_on_any_read: raw_value & int_pin_cfg::INT_RD_CLEAR != 0,
+        }
+    }
+
+    /// Encodes the config into an INT_PIN_CFG register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        let mut value = original_value
+            & !(int_pin_cfg::INT_LEVEL
+                | int_pin_cfg::INT_OPEN
+                | int_pin_cfg::LATCH_INT_EN
+                | int_pin_cfg::INT_RD_CLEAR);
+        if self.level == InterruptLevel::ActiveLow {
+            value |= int_pin_cfg::INT_LEVEL;
+        }
+        if self.drive == InterruptDrive::OpenDrain {
+            value |= int_pin_cfg::INT_OPEN;
+        }
+        if self.latch == InterruptLatch::Latched {
+            value |= int_pin_cfg::LATCH_INT_EN;
+        }
+        if self.clear_on_any_read {
+            value |= int_pin_cfg::INT_RD_CLEAR;
+        }
+        value
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads and clears all interrupt flags in one transaction.
+    ///
+    /// Use this in the interrupt handler to find out why the INT pin fired.
+    /// With a latched INT pin, this also releases the pin.
+    pub fn interrupt_status(&mut self) -> Result<InterruptStatus, Error<DI::Error>> {
+        // FSYNC_INT, INT_PIN_CFG, INT_ENABLE, FIFO_WM_INT_STATUS, INT_STATUS
+        let mut buf: [u8; 5] = [0; 5];
+        self.read_buf(registers::FSYNC_INT, &mut buf)?;
+        let mut status = InterruptStatus::from_int_status(buf[4], self.model);
+        status.set(
+            InterruptStatus::FSYNC,
+            buf[0] & registers::fsync_int::FSYNC_INT != 0,
+        );
+        if self.model.supports(Feature::FifoWatermark) {
+            status.set(
+                InterruptStatus::FIFO_WATERMARK,
+                buf[3] & registers::fifo_wm_int_status::FIFO_WM_INT != 0,
+            );
+        }
+        Ok(status)
+    }
+
+    pub fn set_interrupt_pin_config(
+        &mut self,
+        config: InterruptPinConfig,
+    ) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::INT_PIN_CFG)?;
+        let new_value = config.apply_to_reg(original_value);
+        self.write_u8(registers::INT_PIN_CFG, new_value)
+    }
+
+    pub fn get_interrupt_pin_config(&mut self) -> Result<InterruptPinConfig, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::INT_PIN_CFG)?;
+        Ok(InterruptPinConfig::from_reg(raw_value))
+    }
+
+    /// Enables exactly the given interrupt sources, the others are disabled.
+    ///
+    /// The FIFO watermark interrupt has no enable bit, it fires while the
+    /// watermark set with [`Mpu6886::set_fifo_watermark`] is non-zero. The
+    /// watermark isn't changed here: asking for `FIFO_WATERMARK` fails with
+    /// [`Error::InvalidConfig`] unless a watermark is set, leaving it out
+    /// doesn't clear the watermark. `FSYNC` keeps the level set with
+    /// [`Mpu6886::enable_fsync_interrupt`].
+    pub fn set_interrupt_sources(
+        &mut self,
+        sources: InterruptSources,
+    ) -> Result<(), Error<DI::Error>> {
+        if sources.contains(InterruptSources::FIFO_WATERMARK) {
+            self.model.require(Feature::FifoWatermark)?;
+            if self.get_fifo_watermark()? == 0 {
+                return Err(Error::InvalidConfig(
+                    "set a FIFO watermark to enable its interrupt",
+                ));
+            }
+        }
+        let wom = sources & InterruptSources::WOM;
+        if !wom.is_empty() {
+            self.model.require(Feature::WakeOnMotion)?;
+            if wom != InterruptSources::WOM {
+                self.model.require(Feature::PerAxisWakeOnMotion)?;
+            }
+        }
+        let original_value = self.read_u8(registers::INT_ENABLE)?;
+        let wom_bits = if wom == InterruptSources::WOM {
+            self.model.wom_int_bits()
+        } else {
+            wom.bits() as u8
+        };
+        let enable_bits = int_enable::FIFO_OFLOW_EN | int_enable::DATA_RDY_INT_EN;
+        let new_value = original_value & !(int_enable::WOM_INT_EN | enable_bits)
+            | sources.bits() as u8 & enable_bits
+            | wom_bits;
+        self.write_u8(registers::INT_ENABLE, new_value)?;
+        let original_value = self.read_u8(registers::INT_PIN_CFG)?;
+        let new_value = if sources.contains(InterruptSources::FSYNC) {
+            original_value | int_pin_cfg::FSYNC_INT_MODE_EN
+        } else {
+            original_value & !int_pin_cfg::FSYNC_INT_MODE_EN
+        };
+        self.write_u8(registers::INT_PIN_CFG, new_value)
+    }
+
+    /// Reads the enabled interrupt sources.
+    pub fn get_interrupt_sources(&mut self) -> Result<InterruptSources, Error<DI::Error>> {
+        let enabled = self.read_u8(registers::INT_ENABLE)?;
+        let pin_config = self.read_u8(registers::INT_PIN_CFG)?;
+        let mut sources = InterruptSources::from_bits_truncate(
+            (enabled & (int_enable::FIFO_OFLOW_EN | int_enable::DATA_RDY_INT_EN)) as u16,
+        );
+        let wom = enabled & self.model.wom_int_bits();
+        if self.model.supports(Feature::PerAxisWakeOnMotion) {
+            sources |= InterruptSources::from_bits_truncate(wom as u16);
+        } else if wom != 0 {
+            sources |= InterruptSources::WOM;
+        }
+        sources.set(
+            InterruptSources::FSYNC,
+            pin_config & int_pin_cfg::FSYNC_INT_MODE_EN != 0,
+        );
+        if self.model.supports(Feature::FifoWatermark) {
+            sources.set(
+                InterruptSources::FIFO_WATERMARK,
+                self.get_fifo_watermark()? != 0,
+            );
+        }
+        Ok(sources)
+    }
+
+    /// Fires the INT pin every time new sensor data is available.
+    pub fn enable_data_ready_interrupt(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::INT_ENABLE)?;
+        let new_value = original_value | int_enable::DATA_RDY_INT_EN;
+        self.write_u8(registers::INT_ENABLE, new_value)
+    }
+
+    pub fn disable_data_ready_interrupt(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::INT_ENABLE)?;
+        let new_value = original_value & !int_enable::DATA_RDY_INT_EN;
+        self.write_u8(registers::INT_ENABLE, new_value)
+    }
+
+    /// Polls DATA_RDY_INT every 100 µs until a new sample is available, for
+    /// up to `timeout_us`.
+    ///
+    /// Reading INT_STATUS also clears the other interrupt flags.
+    pub fn wait_for_data_ready(
+        &mut self,
+        delay: &mut impl DelayNs,
+        timeout_us: u32,
+    ) -> Result<(), Error<DI::Error>> {
+        let mut waited_us = 0;
+        loop {
+            if self.read_u8(registers::INT_STATUS)? & int_status::DATA_RDY_INT != 0 {
+                return Ok(());
+            }
+            if waited_us >= timeout_us {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(100);
+            waited_us = waited_us.saturating_add(100);
+        }
+    }
+
+    /// Polls `int_pin` every 10 µs until it is asserted, for up to
+    /// `timeout_us`, then clears the interrupt and reads all sensors.
+    ///
+    /// Expects the data-ready interrupt enabled, the active level is taken
+    /// from the INT pin config. Polling the pin instead of INT_STATUS keeps
+    /// the bus free while waiting. Reading INT_STATUS releases a latched pin
+    /// and also clears the other interrupt flags. Pin errors are reported as
+    /// [`Error::Other`].
+    pub fn read_when_ready(
+        &mut self,
+        int_pin: &mut impl InputPin,
+        delay: &mut impl DelayNs,
+        timeout_us: u32,
+    ) -> Result<Measurement, Error<DI::Error>> {
+        let active_low = self.get_interrupt_pin_config()?.level == InterruptLevel::ActiveLow;
+        let mut waited_us: u32 = 0;
+        loop {
+            let asserted = if active_low {
+                int_pin.is_low()
+            } else {
+                int_pin.is_high()
+            };
+            if asserted.map_err(|_| Error::Other)? {
+                break;
+            }
+            if waited_us >= timeout_us {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(10);
+            waited_us = waited_us.saturating_add(10);
+        }
+        self.read_u8(registers::INT_STATUS)?;
+        self.read_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::MPU6886_DEFAULT_ADDR;
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+
+    fn read(reg: u8, value: &[u8]) -> Transaction {
+        Transaction::write_read(MPU6886_DEFAULT_ADDR, vec![reg], value.to_vec())
+    }
+
+    fn write(reg: u8, value: u8) -> Transaction {
+        Transaction::write(MPU6886_DEFAULT_ADDR, vec![reg, value])
+    }
+
+    #[test]
+    fn sources_leave_fifo_watermark_alone() {
+        // no watermark access, whether one is set or not
+        let expectations = [
+            read(registers::INT_ENABLE, &[0b00010000]),
+            write(registers::INT_ENABLE, 0b00000001),
+            read(registers::INT_PIN_CFG, &[0b00000100]),
+            write(registers::INT_PIN_CFG, 0),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        sensor
+            .set_interrupt_sources(InterruptSources::DATA_READY)
+            .unwrap();
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn fifo_watermark_source_needs_a_watermark() {
+        let expectations = [
+            read(registers::FIFO_WM_TH1, &[0, 0]),
+            read(registers::FIFO_WM_TH1, &[0, 64]),
+            read(registers::INT_ENABLE, &[0]),
+            write(registers::INT_ENABLE, 0),
+            read(registers::INT_PIN_CFG, &[0]),
+            write(registers::INT_PIN_CFG, 0),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        let result = sensor.set_interrupt_sources(InterruptSources::FIFO_WATERMARK);
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+        sensor
+            .set_interrupt_sources(InterruptSources::FIFO_WATERMARK)
+            .unwrap();
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn status_keeps_per_axis_wake_on_motion_flags() {
+        let status = InterruptStatus::from_int_status(0b01000001, Model::Mpu6886);
+        assert_eq!(status, InterruptStatus::WOM_Y | InterruptStatus::DATA_READY);
+    }
+
+    #[test]
+    fn status_spreads_single_wake_on_motion_flag() {
+        let status = InterruptStatus::from_int_status(0b01010000, Model::Mpu6500);
+        assert_eq!(
+            status,
+            InterruptStatus::WOM_X
+                | InterruptStatus::WOM_Y
+                | InterruptStatus::WOM_Z
+                | InterruptStatus::FIFO_OVERFLOW
+        );
+        // the MPU6500 has no flag on bits 7 and 5
+        let status = InterruptStatus::from_int_status(0b10100000, Model::Mpu6500);
+        assert!(status.is_empty());
+    }
+
+    #[test]
+    fn status_ignores_wake_on_motion_without_support() {
+        let status = InterruptStatus::from_int_status(0b11100101, Model::Mpu6050);
+        assert_eq!(
+            status,
+            InterruptStatus::GDRIVE | InterruptStatus::DATA_READY
+        );
+    }
+
+    #[test]
+    fn status_ignores_reserved_bits() {
+        let status = InterruptStatus::from_int_status(0b00001010, Model::Mpu6886);
+        assert!(status.is_empty());
+    }
+}
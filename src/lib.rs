@@ -3,168 +3,728 @@
 #![no_std]
 
 mod accelerometer;
+#[cfg(feature = "accelerometer")]
+mod accelerometer_traits;
+#[cfg(feature = "analysis")]
+pub mod analysis;
+#[cfg(feature = "async")]
+mod asynch;
+mod cache;
+mod calibration;
+mod config;
+mod fifo;
+mod fsync;
+#[cfg(feature = "fusion")]
+mod fusion;
 mod gyroscope;
+mod interface;
+mod interrupt;
+mod measurement;
+mod model;
+mod motion;
+mod mounting;
+#[cfg(feature = "orientation")]
+mod orientation;
+mod power;
+mod profile;
+#[cfg(feature = "heapless")]
+mod queue;
+mod rate;
+pub mod registers;
+mod reset;
+mod self_test;
+mod split;
+mod stats;
+#[cfg(feature = "embedded-io")]
+mod telemetry;
+pub mod typestate;
+#[cfg(any(feature = "mint", feature = "nalgebra"))]
+mod vector;
+#[cfg(feature = "postcard")]
+mod wire;
 
-use accelerometer::AccelScaleRange;
-use gyroscope::GyroScaleRange;
+pub use accelerometer::{
+    AccelAveraging, AccelBandwidth, AccelOffsets, AccelScaleRange, ACCEL_SENSITIVITY_16G,
+    ACCEL_SENSITIVITY_2G, ACCEL_SENSITIVITY_4G, ACCEL_SENSITIVITY_8G,
+};
+#[cfg(feature = "async")]
+pub use asynch::Mpu6886Async;
+pub use calibration::{
+    AccelCalibration, CalibrationData, CalibrationPosition, GyroTemperatureTable,
+    SixPositionCalibration,
+};
+pub use config::Mpu6886Config;
+pub use fifo::{FifoConfig, FifoMode, FifoPackets, FifoSample, FifoSamples};
+pub use fsync::ExtSync;
+#[cfg(feature = "fusion")]
+pub use fusion::{Madgwick, Mahony, Quaternion};
+pub use gyroscope::{
+    GyroAveraging, GyroBandwidth, GyroOffsets, GyroScaleRange, GYRO_SENSITIVITY_1000DPS,
+    GYRO_SENSITIVITY_2000DPS, GYRO_SENSITIVITY_250DPS, GYRO_SENSITIVITY_500DPS,
+};
+pub use interface::{I2cInterface, Interface, Retry, SpiInterface};
+pub use interrupt::{
+    InterruptDrive, InterruptLatch, InterruptLevel, InterruptPinConfig, InterruptSources,
+    InterruptStatus,
+};
+pub use measurement::{Measurement, RawAccelGyroFrame, RawMeasurement};
+pub use model::{DeviceInfo, Model};
+pub use motion::WakeOnMotionMode;
+pub use mounting::{Axis, AxisMap, IDENTITY_MATRIX};
+
+use cache::RegisterCache;
+use mounting::Mounting;
+#[cfg(feature = "orientation")]
+pub use orientation::ComplementaryFilter;
+pub use power::{ClockSource, PowerMode, SensorAxes};
+pub use profile::Profile;
+#[cfg(feature = "heapless")]
+pub use queue::SampleQueue;
+pub use rate::OutputDataRate;
+pub use reset::SignalPath;
+pub use self_test::SelfTestResult;
+pub use split::{Mpu6886Control, Mpu6886Reader};
+pub use stats::{AxisStats, SampleStats};
+#[cfg(feature = "embedded-io")]
+pub use telemetry::{SampleFormat, SampleWriter};
 
 use embedded_hal::i2c::{Error as I2cError, ErrorKind as I2cErrorKind, I2c};
+use embedded_hal::spi::SpiDevice;
+
+use core::fmt::Debug;
 
+/// I2C address with AD0 pulled low.
 pub const MPU6886_DEFAULT_ADDR: u8 = 0x68;
+/// I2C address with AD0 pulled high.
+pub const MPU6886_ALTERNATE_ADDR: u8 = 0x69;
+/// WHO_AM_I value of the MPU6886.
+pub const MPU6886_WHO_AM_I: u8 = 0x19;
 
-/// MPU6886 error type.
+/// MPU6886 error type, generic over the bus error.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
-pub enum Error {
-    /// An I2C error occurred during the transaction.
-    I2cError(I2cErrorKind),
+pub enum Error<E> {
+    /// A bus error occurred while accessing a register, the original I2C or
+    /// SPI error is preserved.
+    Transfer { operation: BusOperation, error: E },
     /// Unknown chip detect.
     UnknownChip(u8),
+    /// The chip didn't get ready in time.
+    Timeout,
+    /// Sensor data was read before [`Mpu6886::init`] or [`Mpu6886::wake`], or
+    /// after a reset.
+    NotInitialized,
+    /// Sensor data was read while the chip sleeps, see [`Mpu6886::wake`].
+    Asleep,
+    /// The configuration has conflicting settings.
+    InvalidConfig(&'static str),
+    /// A register read back differently than written, see
+    /// [`Mpu6886::enable_write_verification`].
+    VerificationFailed { reg: u8, expected: u8, got: u8 },
     /// Other error. The original error converted from may contain more information.
     Other,
 }
 
-impl<T: I2cError> From<T> for Error {
-    fn from(value: T) -> Self {
-        Self::I2cError(value.kind())
+/// Register access that failed, see [`Error::Transfer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusOperation {
+    /// Reading, starting at the register.
+    ReadReg(u8),
+    /// Writing, starting at the register.
+    WriteReg(u8),
+}
+
+impl<E> Error<E> {
+    /// Returns the register access that failed, for bus errors.
+    pub fn operation(&self) -> Option<BusOperation> {
+        match self {
+            Error::Transfer { operation, .. } => Some(*operation),
+            _ => None,
+        }
+    }
+}
+
+impl<E: I2cError> Error<E> {
+    /// Returns the generic I2C error kind, if this is a bus error.
+    pub fn i2c_error_kind(&self) -> Option<I2cErrorKind> {
+        match self {
+            Error::Transfer { error, .. } => Some(error.kind()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this is a bus error likely to go away on retry:
+    /// missing acknowledge, arbitration loss or a bus error.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.i2c_error_kind(),
+            Some(
+                I2cErrorKind::NoAcknowledge(_) | I2cErrorKind::ArbitrationLoss | I2cErrorKind::Bus
+            )
+        )
+    }
+}
+
+impl<E: Debug> core::fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Transfer {
+                operation: BusOperation::ReadReg(reg),
+                error,
+            } => write!(f, "bus error reading {reg:#04x}: {error:?}"),
+            Error::Transfer {
+                operation: BusOperation::WriteReg(reg),
+                error,
+            } => write!(f, "bus error writing {reg:#04x}: {error:?}"),
+            Error::UnknownChip(id) => write!(f, "unknown chip, WHO_AM_I is {id:#04x}"),
+            Error::Timeout => write!(f, "timed out waiting for the chip"),
+            Error::NotInitialized => write!(f, "driver not initialized"),
+            Error::Asleep => write!(f, "chip is asleep"),
+            Error::InvalidConfig(reason) => write!(f, "invalid configuration: {reason}"),
+            Error::VerificationFailed { reg, expected, got } => write!(
+                f,
+                "register {reg:#04x} read back {got:#04x}, expected {expected:#04x}"
+            ),
+            Error::Other => write!(f, "other error"),
+        }
     }
 }
 
-impl embedded_hal::digital::Error for Error {
+impl<E: Debug> core::error::Error for Error<E> {}
+
+impl<E: Debug> embedded_hal::digital::Error for Error<E> {
     fn kind(&self) -> embedded_hal::digital::ErrorKind {
         embedded_hal::digital::ErrorKind::Other
     }
 }
 
 #[derive(Debug)]
-pub struct Mpu6886<I2C> {
-    i2c: I2C,
-    address: u8,
+pub struct Mpu6886<DI> {
+    iface: DI,
     acc_range: AccelScaleRange,
     gyro_range: GyroScaleRange,
+    fifo_config: FifoConfig,
+    calibration: CalibrationData,
+    model: Model,
+    mounting: Mounting,
+    accel_dead_band: u16,
+    cache: RegisterCache,
+    last_config: Option<Mpu6886Config>,
+    verify_writes: bool,
+    max_read_len: Option<usize>,
+    initialized: bool,
+    asleep: bool,
 }
 
-impl<I2C: I2c> Mpu6886<I2C> {
+impl<I2C: I2c> Mpu6886<I2cInterface<I2C>> {
+    /// Creates a driver for a chip at [`MPU6886_DEFAULT_ADDR`].
     #[must_use]
     pub fn new(i2c: I2C) -> Self {
-        Self {
-            i2c,
-            address: MPU6886_DEFAULT_ADDR,
-            acc_range: AccelScaleRange::Range2g,
-            gyro_range: GyroScaleRange::Range250Dps,
-        }
+        Self::new_with_addr(i2c, MPU6886_DEFAULT_ADDR)
     }
 
+    /// Creates a driver for a chip at the given address, e.g.
+    /// [`MPU6886_ALTERNATE_ADDR`].
     #[must_use]
     pub fn new_with_addr(i2c: I2C, address: u8) -> Self {
+        Self::new_with_model(i2c, address, Model::Mpu6886)
+    }
+
+    /// Creates a driver for another chip of the family at the given address.
+    #[must_use]
+    pub fn new_with_model(i2c: I2C, address: u8, model: Model) -> Self {
+        Self::new_with_interface(I2cInterface::new(i2c, address), model)
+    }
+
+    pub fn destroy(self) -> I2C {
+        self.iface.release()
+    }
+
+    /// Reads WHO_AM_I of the chip at `address`, `None` if nothing answers.
+    fn read_who_am_i_at(i2c: &mut I2C, address: u8) -> Result<Option<u8>, Error<I2C::Error>> {
+        let mut buf: [u8; 1] = [0; 1];
+        match i2c.write_read(address, &[registers::WHO_AM_I], &mut buf) {
+            Ok(_) => Ok(Some(buf[0])),
+            Err(e) => match e.kind() {
+                I2cErrorKind::NoAcknowledge(_) => Ok(None),
+                _ => Err(Error::Transfer {
+                    operation: BusOperation::ReadReg(registers::WHO_AM_I),
+                    error: e,
+                }),
+            },
+        }
+    }
+
+    /// Returns whether an MPU6886 answers at `address`.
+    ///
+    /// A missing acknowledge counts as absent, other bus errors are returned.
+    pub fn is_present(i2c: &mut I2C, address: u8) -> Result<bool, Error<I2C::Error>> {
+        Ok(Self::read_who_am_i_at(i2c, address)? == Some(MPU6886_WHO_AM_I))
+    }
+
+    /// Returns the first address a known chip answers at, and its model,
+    /// checking [`MPU6886_DEFAULT_ADDR`] then [`MPU6886_ALTERNATE_ADDR`].
+    ///
+    /// Use [`Mpu6886::is_present`] to check both chips of a two-device bus.
+    pub fn probe(i2c: &mut I2C) -> Result<Option<(u8, Model)>, Error<I2C::Error>> {
+        for address in [MPU6886_DEFAULT_ADDR, MPU6886_ALTERNATE_ADDR] {
+            let model = Self::read_who_am_i_at(i2c, address)?.and_then(Model::from_who_am_i);
+            if let Some(model) = model {
+                return Ok(Some((address, model)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<SPI: SpiDevice> Mpu6886<SpiInterface<SPI>> {
+    /// Creates a driver for a chip on an SPI bus.
+    #[must_use]
+    pub fn new_spi(spi: SPI) -> Self {
+        Self::new_with_interface(SpiInterface::new(spi), Model::Mpu6886)
+    }
+
+    pub fn destroy(self) -> SPI {
+        self.iface.release()
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Creates a driver talking through the given bus interface.
+    #[must_use]
+    pub fn new_with_interface(iface: DI, model: Model) -> Self {
         Self {
-            i2c,
-            address,
+            iface,
             acc_range: AccelScaleRange::Range2g,
             gyro_range: GyroScaleRange::Range250Dps,
+            fifo_config: FifoConfig::default(),
+            calibration: CalibrationData::default(),
+            model,
+            mounting: Mounting::default(),
+            accel_dead_band: 0,
+            cache: RegisterCache::default(),
+            last_config: None,
+            verify_writes: false,
+            max_read_len: None,
+            initialized: false,
+            asleep: true,
         }
     }
 
-    pub fn destroy(self) -> I2C {
-        self.i2c
+    /// Gives back the bus interface.
+    pub fn release_interface(self) -> DI {
+        self.iface
+    }
+
+    /// Returns the chip model the driver was created for.
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    /// Reads the WHO_AM_I register.
+    pub fn who_am_i(&mut self) -> Result<u8, Error<DI::Error>> {
+        self.read_u8(registers::WHO_AM_I)
     }
 
     /// Checks chip version and load current state.
-    pub fn init(&mut self) -> Result<(), Error> {
-        let chip_id = self.read_u8(0x75)?;
-        if chip_id != 0x19 {
+    pub fn init(&mut self) -> Result<(), Error<DI::Error>> {
+        self.init_with_ids(&[self.model.who_am_i()])
+    }
+
+    /// Like [`Mpu6886::init`], but accepts any of the given WHO_AM_I values,
+    /// for clones reporting a different ID.
+    pub fn init_with_ids(&mut self, accepted_ids: &[u8]) -> Result<(), Error<DI::Error>> {
+        let chip_id = self.who_am_i()?;
+        if !accepted_ids.contains(&chip_id) {
             Err(Error::UnknownChip(chip_id))
         } else {
-            self.acc_range = self.get_accel_scale_range()?;
-            self.gyro_range = self.get_gyro_scale_range()?;
-            Ok(())
+            self.init_unchecked()
         }
     }
 
+    /// Loads current state without checking the chip version.
+    pub fn init_unchecked(&mut self) -> Result<(), Error<DI::Error>> {
+        self.acc_range = self.get_accel_scale_range()?;
+        self.gyro_range = self.get_gyro_scale_range()?;
+        self.asleep = self.read_u8(registers::PWR_MGMT_1)? & registers::pwr_mgmt_1::SLEEP != 0;
+        self.initialized = true;
+        Ok(())
+    }
+
     /// Resets the sensor to initial state.
-    pub fn reset(&mut self) -> Result<(), Error> {
-        self.write_u8(0x6B, 0b10000000)?;
+    ///
+    /// The chip comes back asleep, call [`Mpu6886::init`] again once it's
+    /// ready, or use [`Mpu6886::reset_and_wait`] which does both.
+    pub fn reset(&mut self) -> Result<(), Error<DI::Error>> {
+        self.write_u8(registers::PWR_MGMT_1, registers::pwr_mgmt_1::DEVICE_RESET)?;
         // also reset internal state
         self.acc_range = AccelScaleRange::Range2g;
         self.gyro_range = GyroScaleRange::Range250Dps;
+        self.fifo_config = FifoConfig::default();
+        self.asleep = true;
+        self.initialized = false;
         Ok(())
     }
 
-    pub fn sleep(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value | 0b01000000;
-        self.write_u8(0x6B, new_value)
+    #[deprecated(note = "use `set_power_mode(PowerMode::Sleep)`")]
+    pub fn sleep(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value | registers::pwr_mgmt_1::SLEEP;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
     }
 
     /// Wakes the inertial sensor up.
     ///
     /// The sensor is in sleep mode by default. For lazy people who don't
     /// want to check sensor's version, this method also loads current range
-    /// states from chip so the values are calculated correctly.
-    pub fn wake(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value & 0b10111111;
-        self.write_u8(0x6B, new_value)?;
+    /// states from chip so the values are calculated correctly, and
+    /// measurements work without [`Mpu6886::init`].
+    #[deprecated(note = "use `init` and `set_power_mode(PowerMode::SixAxisLowNoise)`")]
+    pub fn wake(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value & !registers::pwr_mgmt_1::SLEEP;
+        self.write_u8(registers::PWR_MGMT_1, new_value)?;
         // also load state from chip
         self.acc_range = self.get_accel_scale_range()?;
         self.gyro_range = self.get_gyro_scale_range()?;
+        self.initialized = true;
         Ok(())
     }
 
-    pub fn use_best_clock(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value & 0b11111000 | 0b00000001;
-        self.write_u8(0x6B, new_value)
+    pub fn disable_temperature_sensor(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value | registers::pwr_mgmt_1::TEMP_DIS;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
+    }
+
+    pub fn enable_temperature_sensor(&mut self) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value & !registers::pwr_mgmt_1::TEMP_DIS;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
     }
 
-    pub fn use_internal_clock(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value & 0b11111000;
-        self.write_u8(0x6B, new_value)
+    pub fn temperature(&mut self) -> Result<f32, Error<DI::Error>> {
+        let raw_value = self.temperature_raw()?;
+        Ok(self.model.scale_temperature(raw_value))
     }
 
-    pub fn disable_temperature_sensor(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value | 0b00001000;
-        self.write_u8(0x6B, new_value)
+    /// Returns the raw, signed temperature reading.
+    pub fn temperature_raw(&mut self) -> Result<i16, Error<DI::Error>> {
+        self.check_ready()?;
+        let raw_value = self.read_u16(registers::TEMP_OUT_H)?;
+        Ok(raw_value as i16)
     }
 
-    pub fn enable_temperature_sensor(&mut self) -> Result<(), Error> {
-        let original_value = self.read_u8(0x6B)?;
-        let new_value = original_value & 0b11110111;
-        self.write_u8(0x6B, new_value)
+    /// Reads a register directly.
+    pub fn read_register(&mut self, reg: u8) -> Result<u8, Error<DI::Error>> {
+        self.read_u8(reg)
     }
 
-    pub fn temperature(&mut self) -> Result<f32, Error> {
-        let raw_value = self.read_u16(0x41)?;
-        let temperature: f32 = raw_value as i16 as f32 / 326.8 + 25.0;
-        Ok(temperature)
+    /// Writes a register directly, for features the driver doesn't model.
+    ///
+    /// The driver caches the scale ranges and FIFO layout, call
+    /// [`Mpu6886::reload_state`] after changing ACCEL_CONFIG, GYRO_CONFIG or
+    /// FIFO_EN this way.
+    pub fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<DI::Error>> {
+        self.write_u8(reg, value)
+    }
+
+    /// Reads every register write back and fails with
+    /// [`Error::VerificationFailed`] on a mismatch.
+    ///
+    /// Self-clearing reset bits and the FIFO data register are not checked.
+    pub fn enable_write_verification(&mut self) {
+        self.verify_writes = true;
+    }
+
+    pub fn disable_write_verification(&mut self) {
+        self.verify_writes = false;
+    }
+
+    /// Splits burst reads into transfers of at most `max_len` bytes, for bus
+    /// controllers with a transfer size limit. `None` reads in one transfer.
+    ///
+    /// Register reads continue at the next address, FIFO reads re-read
+    /// FIFO_R_W.
+    pub fn set_max_read_len(&mut self, max_len: Option<usize>) {
+        self.max_read_len = max_len.map(|len| len.max(1));
+    }
+
+    /// Reloads the cached scale ranges and FIFO layout from the chip.
+    pub fn reload_state(&mut self) -> Result<(), Error<DI::Error>> {
+        self.acc_range = self.get_accel_scale_range()?;
+        self.gyro_range = self.get_gyro_scale_range()?;
+        self.fifo_config = self.get_fifo_config()?;
+        Ok(())
+    }
+
+    /// Fails unless the driver is initialized and the chip awake, so sensor
+    /// data is never decoded with stale ranges.
+    pub(crate) fn check_ready(&self) -> Result<(), Error<DI::Error>> {
+        check_ready(self.initialized, self.asleep)
+    }
+
+    /// Tracks the power state from PWR_MGMT_1 writes, a reset also drops the
+    /// loaded state.
+    fn track_write(&mut self, reg: u8, value: u8) {
+        if reg == registers::PWR_MGMT_1 {
+            self.asleep =
+                value & (registers::pwr_mgmt_1::DEVICE_RESET | registers::pwr_mgmt_1::SLEEP) != 0;
+            if value & registers::pwr_mgmt_1::DEVICE_RESET != 0 {
+                self.initialized = false;
+            }
+        }
     }
 
     /// Reads one u8 integer.
-    fn read_u8(&mut self, reg: u8) -> Result<u8, Error> {
+    fn read_u8(&mut self, reg: u8) -> Result<u8, Error<DI::Error>> {
+        if let Some(value) = self.cache.get(reg) {
+            return Ok(value);
+        }
         let mut buf: [u8; 1] = [0; 1];
+        self.read_buf(reg, &mut buf)?;
+        self.cache.update(reg, buf[0]);
+        Ok(buf[0])
+    }
 
-        match self.i2c.write_read(self.address, &[reg], &mut buf) {
-            Ok(_) => Ok(buf[0]),
-            Err(e) => Err(e.into()),
+    fn write_u8(&mut self, reg: u8, value: u8) -> Result<(), Error<DI::Error>> {
+        #[cfg(feature = "trace")]
+        trace_write(reg, value);
+        self.iface.write_register(reg, value)?;
+        self.cache.update(reg, value);
+        self.track_write(reg, value);
+        self.verify_write(reg, value)
+    }
+
+    /// Reads `reg` back if write verification is enabled.
+    fn verify_write(&mut self, reg: u8, value: u8) -> Result<(), Error<DI::Error>> {
+        if !self.verify_writes {
+            return Ok(());
+        }
+        // bits that don't read back as written
+        let mask = match reg {
+            registers::PWR_MGMT_1 if value & registers::pwr_mgmt_1::DEVICE_RESET != 0 => {
+                return Ok(())
+            }
+            registers::SIGNAL_PATH_RESET | registers::FIFO_R_W => return Ok(()),
+            registers::USER_CTRL => {
+                !(registers::user_ctrl::FIFO_RST | registers::user_ctrl::SIG_COND_RST)
+            }
+            _ => u8::MAX,
+        };
+        let mut buf: [u8; 1] = [0; 1];
+        self.read_buf(reg, &mut buf)?;
+        if buf[0] & mask != value & mask {
+            return Err(Error::VerificationFailed {
+                reg,
+                expected: value,
+                got: buf[0],
+            });
         }
+        Ok(())
     }
 
-    fn write_u8(&mut self, reg: u8, value: u8) -> Result<(), Error> {
-        Ok(self.i2c.write(self.address, &[reg, value])?)
+    /// Writes consecutive registers in one transaction.
+    fn write_buf(&mut self, reg: u8, data: &[u8]) -> Result<(), Error<DI::Error>> {
+        #[cfg(feature = "trace")]
+        for (offset, value) in data.iter().enumerate() {
+            trace_write(reg + offset as u8, *value);
+        }
+        self.iface.write_registers(reg, data)?;
+        for (offset, value) in data.iter().enumerate() {
+            self.cache.update(reg + offset as u8, *value);
+            self.track_write(reg + offset as u8, *value);
+            self.verify_write(reg + offset as u8, *value)?;
+        }
+        Ok(())
     }
 
-    fn read_u16(&mut self, reg: u8) -> Result<u16, Error> {
+    fn read_u16(&mut self, reg: u8) -> Result<u16, Error<DI::Error>> {
         let mut buf: [u8; 2] = [0; 2];
         self.read_buf(reg, &mut buf)?;
         let value: u16 = ((buf[0] as u16) << 8) + (buf[1] as u16);
         Ok(value)
     }
 
-    #[inline]
-    fn read_buf(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error> {
-        Ok(self.i2c.write_read(self.address, &[reg], buf)?)
+    fn read_buf(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<DI::Error>> {
+        read_chunked(&mut self.iface, reg, buf, self.max_read_len)?;
+        #[cfg(feature = "trace")]
+        trace_read(reg, buf);
+        Ok(())
+    }
+}
+
+/// Fails with [`Error::NotInitialized`] or [`Error::Asleep`] unless the
+/// driver state allows reading sensor data.
+pub(crate) fn check_ready<E>(initialized: bool, asleep: bool) -> Result<(), Error<E>> {
+    if !initialized {
+        Err(Error::NotInitialized)
+    } else if asleep {
+        Err(Error::Asleep)
+    } else {
+        Ok(())
+    }
+}
+
+/// Burst reads `buf` from `reg`, split into transfers of at most
+/// `max_read_len` bytes.
+pub(crate) fn read_chunked<DI: Interface>(
+    iface: &mut DI,
+    reg: u8,
+    buf: &mut [u8],
+    max_read_len: Option<usize>,
+) -> Result<(), Error<DI::Error>> {
+    match max_read_len {
+        Some(max_len) if buf.len() > max_len => {
+            for (index, chunk) in buf.chunks_mut(max_len).enumerate() {
+                iface.read_registers(chunk_register(reg, index, max_len)?, chunk)?;
+            }
+            Ok(())
+        }
+        _ => iface.read_registers(reg, buf),
+    }
+}
+
+/// Returns the first register of chunk `index` of a split burst read, FIFO
+/// reads stay on FIFO_R_W.
+///
+/// Fails with [`Error::InvalidConfig`] if the chunk starts past the last
+/// register.
+pub(crate) fn chunk_register<E>(reg: u8, index: usize, max_len: usize) -> Result<u8, Error<E>> {
+    if reg == registers::FIFO_R_W {
+        return Ok(reg);
+    }
+    index
+        .checked_mul(max_len)
+        .and_then(|offset| u8::try_from(offset).ok())
+        .and_then(|offset| reg.checked_add(offset))
+        .ok_or(Error::InvalidConfig("burst read past the last register"))
+}
+
+#[cfg(feature = "trace")]
+pub(crate) fn trace_read(reg: u8, data: &[u8]) {
+    #[cfg(feature = "defmt")]
+    defmt::trace!("mpu6886 read {=u8:#04x}: {=[u8]:#04x}", reg, data);
+    #[cfg(not(feature = "defmt"))]
+    log::trace!("mpu6886 read {reg:#04x}: {data:#04x?}");
+}
+
+#[cfg(feature = "trace")]
+pub(crate) fn trace_write(reg: u8, value: u8) {
+    #[cfg(feature = "defmt")]
+    defmt::trace!("mpu6886 write {=u8:#04x}: {=u8:#04x}", reg, value);
+    #[cfg(not(feature = "defmt"))]
+    log::trace!("mpu6886 write {reg:#04x}: {value:#04x}");
+}
+
+/// Decodes three big-endian i16 words, as laid out in the sample registers.
+pub(crate) fn xyz_from_be_bytes(buf: &[u8; 6]) -> (i16, i16, i16) {
+    (
+        i16::from_be_bytes([buf[0], buf[1]]),
+        i16::from_be_bytes([buf[2], buf[3]]),
+        i16::from_be_bytes([buf[4], buf[5]]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+
+    #[test]
+    fn reset_drops_loaded_state() {
+        let address = MPU6886_DEFAULT_ADDR;
+        let expectations = [
+            Transaction::write_read(address, vec![registers::ACCEL_CONFIG], vec![0b00011000]),
+            Transaction::write_read(address, vec![registers::GYRO_CONFIG], vec![0b00011000]),
+            Transaction::write_read(address, vec![registers::PWR_MGMT_1], vec![0b00000001]),
+            Transaction::write(address, vec![registers::PWR_MGMT_1, 0b10000000]),
+        ];
+        let mut sensor = Mpu6886::new(Mock::new(&expectations));
+        sensor.init_unchecked().unwrap();
+        sensor.fifo_config = FifoConfig {
+            accel: true,
+            gyro: false,
+        };
+        assert!(sensor.check_ready().is_ok());
+        sensor.reset().unwrap();
+        assert!(matches!(sensor.check_ready(), Err(Error::NotInitialized)));
+        assert!(sensor.asleep);
+        assert_eq!(sensor.fifo_config(), FifoConfig::default());
+        assert_eq!(sensor.accel_scale_range(), AccelScaleRange::Range2g);
+        sensor.destroy().done();
+    }
+
+    #[test]
+    fn chunks_continue_at_next_register() {
+        assert_eq!(
+            chunk_register::<()>(registers::ACCEL_XOUT_H, 0, 4),
+            Ok(0x3B)
+        );
+        assert_eq!(
+            chunk_register::<()>(registers::ACCEL_XOUT_H, 3, 4),
+            Ok(0x47)
+        );
+        assert_eq!(chunk_register::<()>(0xF0, 1, 15), Ok(0xFF));
+    }
+
+    #[test]
+    fn chunks_past_last_register_fail() {
+        assert!(matches!(
+            chunk_register::<()>(0xF0, 1, 16),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            chunk_register::<()>(0, 1, 256),
+            Err(Error::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            chunk_register::<()>(0, usize::MAX, 2),
+            Err(Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn fifo_chunks_stay_on_fifo_register() {
+        assert_eq!(
+            chunk_register::<()>(registers::FIFO_R_W, 1000, 64),
+            Ok(registers::FIFO_R_W)
+        );
+    }
+
+    #[test]
+    fn read_chunked_splits_reads() {
+        let address = MPU6886_DEFAULT_ADDR;
+        let expectations = [
+            Transaction::write_read(address, vec![0x3B], vec![1, 2, 3, 4, 5]),
+            Transaction::write_read(address, vec![0x40], vec![6, 7, 8, 9, 10]),
+            Transaction::write_read(address, vec![0x45], vec![11, 12, 13, 14]),
+            // fits in one transfer
+            Transaction::write_read(address, vec![0x3B], vec![1, 2, 3, 4, 5]),
+        ];
+        let mut iface = I2cInterface::new(Mock::new(&expectations), address);
+        let mut buf = [0; 14];
+        read_chunked(&mut iface, registers::ACCEL_XOUT_H, &mut buf, Some(5)).unwrap();
+        assert_eq!(buf, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14]);
+        let mut buf = [0; 5];
+        read_chunked(&mut iface, registers::ACCEL_XOUT_H, &mut buf, Some(5)).unwrap();
+        iface.release().done();
+    }
+
+    #[test]
+    fn read_chunked_rejects_reads_past_last_register() {
+        let address = MPU6886_DEFAULT_ADDR;
+        let expectations = [Transaction::write_read(address, vec![0xFE], vec![1, 2])];
+        let mut iface = I2cInterface::new(Mock::new(&expectations), address);
+        let mut buf = [0; 3];
+        let result = read_chunked(&mut iface, 0xFE, &mut buf, Some(2));
+        assert!(matches!(result, Err(Error::InvalidConfig(_))));
+        iface.release().done();
     }
 }
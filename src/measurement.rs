@@ -0,0 +1,202 @@
+//! Combined measurement implementation.
+
+use crate::accelerometer::{apply_dead_band, scale_acceleration};
+use crate::gyroscope::scale_gyro;
+use crate::mounting::Mounting;
+use crate::{
+    registers, xyz_from_be_bytes, AccelScaleRange, CalibrationData, Error, GyroScaleRange,
+    Interface, Model, Mpu6886,
+};
+
+/// Accelerometer, temperature and gyroscope readings from the same sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Measurement {
+    /// Acceleration, (X, Y, Z), in m/s².
+    pub accel: (f32, f32, f32),
+    /// Angular rate, (X, Y, Z), in rad/s.
+    pub gyro: (f32, f32, f32),
+    /// Temperature in °C.
+    pub temp: f32,
+}
+
+/// Raw accelerometer, temperature and gyroscope readings from the same
+/// sample.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawMeasurement {
+    pub accel: (i16, i16, i16),
+    pub gyro: (i16, i16, i16),
+    pub temp: i16,
+}
+
+/// The 14 sample registers as read in one burst from
+/// [`registers::ACCEL_XOUT_H`], big-endian accel, temperature and gyro.
+///
+/// The bytes are public so a DMA transfer can fill them in place, the
+/// accessors decode without copying the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(C)]
+pub struct RawAccelGyroFrame(pub [u8; 14]);
+
+impl RawAccelGyroFrame {
+    /// Length of the burst, in bytes.
+    pub const SIZE: usize = 14;
+    /// First register of the burst.
+    pub const BASE_REGISTER: u8 = registers::ACCEL_XOUT_H;
+
+    pub fn as_bytes(&self) -> &[u8; 14] {
+        &self.0
+    }
+
+    pub fn as_mut_bytes(&mut self) -> &mut [u8; 14] {
+        &mut self.0
+    }
+
+    pub fn accel(&self) -> (i16, i16, i16) {
+        xyz_from_be_bytes(self.0[0..6].try_into().unwrap())
+    }
+
+    pub fn temp(&self) -> i16 {
+        i16::from_be_bytes([self.0[6], self.0[7]])
+    }
+
+    pub fn gyro(&self) -> (i16, i16, i16) {
+        xyz_from_be_bytes(self.0[8..14].try_into().unwrap())
+    }
+}
+
+impl From<RawAccelGyroFrame> for RawMeasurement {
+    fn from(frame: RawAccelGyroFrame) -> Self {
+        Self {
+            accel: frame.accel(),
+            gyro: frame.gyro(),
+            temp: frame.temp(),
+        }
+    }
+}
+
+/// Fixed-width output, so consecutive lines stay aligned:
+/// `accel:   +0.012   -0.034   +9.807 m/s², gyro:  +0.001 ...`.
+impl core::fmt::Display for Measurement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (ax, ay, az) = self.accel;
+        let (gx, gy, gz) = self.gyro;
+        write!(
+            f,
+            "accel: {ax:+8.3} {ay:+8.3} {az:+8.3} m/s², \
+             gyro: {gx:+7.3} {gy:+7.3} {gz:+7.3} rad/s, temp: {:+6.2} °C",
+            self.temp
+        )
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads accelerometer, temperature and gyroscope in one transaction.
+    ///
+    /// All values come from the same sample instant.
+    pub fn read_all(&mut self) -> Result<Measurement, Error<DI::Error>> {
+        let raw = self.read_all_raw()?;
+        Ok(self.scale_measurement(raw))
+    }
+
+    /// Reads all sensors if a new sample is available, returns
+    /// `WouldBlock` otherwise.
+    ///
+    /// Reading INT_STATUS also clears the other interrupt flags.
+    #[cfg(feature = "nb")]
+    pub fn try_read_all(&mut self) -> nb::Result<Measurement, Error<DI::Error>> {
+        if self.read_u8(registers::INT_STATUS)? & registers::int_status::DATA_RDY_INT == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(self.read_all()?)
+    }
+
+    /// Reads raw accelerometer, temperature and gyroscope readings in one
+    /// transaction.
+    pub fn read_all_raw(&mut self) -> Result<RawMeasurement, Error<DI::Error>> {
+        let mut frame = RawAccelGyroFrame::default();
+        self.read_frame(&mut frame)?;
+        Ok(frame.into())
+    }
+
+    /// Reads the sample registers into `frame` without decoding them.
+    pub fn read_frame(&mut self, frame: &mut RawAccelGyroFrame) -> Result<(), Error<DI::Error>> {
+        self.check_ready()?;
+        self.read_buf(RawAccelGyroFrame::BASE_REGISTER, &mut frame.0)
+    }
+
+    /// Converts raw readings with the current ranges, calibration, mounting
+    /// correction and accelerometer dead-band.
+    pub fn scale_measurement(&self, raw: RawMeasurement) -> Measurement {
+        self.conversion().measurement(raw)
+    }
+
+    pub(crate) fn conversion(&self) -> Conversion<'_> {
+        Conversion::new(
+            self.acc_range,
+            self.gyro_range,
+            &self.calibration,
+            &self.mounting,
+            self.model,
+            self.accel_dead_band,
+        )
+    }
+}
+
+/// Driver state converting raw readings, shared by the blocking, async and
+/// split drivers.
+pub(crate) struct Conversion<'a> {
+    acc_range: AccelScaleRange,
+    gyro_range: GyroScaleRange,
+    calibration: &'a CalibrationData,
+    mounting: &'a Mounting,
+    model: Model,
+    accel_dead_band: u16,
+}
+
+impl<'a> Conversion<'a> {
+    pub(crate) fn new(
+        acc_range: AccelScaleRange,
+        gyro_range: GyroScaleRange,
+        calibration: &'a CalibrationData,
+        mounting: &'a Mounting,
+        model: Model,
+        accel_dead_band: u16,
+    ) -> Self {
+        Self {
+            acc_range,
+            gyro_range,
+            calibration,
+            mounting,
+            model,
+            accel_dead_band,
+        }
+    }
+
+    /// Applies the range, calibration, mounting correction and dead-band, in
+    /// that order. Returns m/s².
+    pub(crate) fn acceleration(&self, raw: (i16, i16, i16)) -> (f32, f32, f32) {
+        let value = scale_acceleration(raw, self.acc_range);
+        let value = self.mounting.apply(self.calibration.apply_accel(value));
+        apply_dead_band(value, self.accel_dead_band)
+    }
+
+    /// Applies the range, calibration and mounting correction, in that
+    /// order. Returns rad/s.
+    pub(crate) fn gyro(&self, raw: (i16, i16, i16)) -> (f32, f32, f32) {
+        let value = scale_gyro(raw, self.gyro_range);
+        self.mounting.apply(self.calibration.apply_gyro(value))
+    }
+
+    pub(crate) fn measurement(&self, raw: RawMeasurement) -> Measurement {
+        Measurement {
+            accel: self.acceleration(raw.accel),
+            gyro: self.gyro(raw.gyro),
+            temp: self.model.scale_temperature(raw.temp),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! MPU6xxx family support.
+
+use crate::registers::fifo_en;
+use crate::{registers, AccelScaleRange, ClockSource, Error, GyroScaleRange, Interface, Mpu6886};
+
+/// Chips of the MPU6xxx family the driver can talk to.
+///
+/// They share the sample registers, scale ranges, sample rate divider and
+/// gyroscope DLPF. FIFO_EN differs, the driver encodes it per model so FIFO
+/// packets have the same layout on all of them. Beyond that:
+///
+/// - the MPU6500 has a single wake-on-motion threshold and flag, and no
+///   accelerometer averaging, gyroscope low power mode or FIFO watermark.
+///   Its DLPF bandwidths differ slightly from the names of
+///   [`AccelBandwidth`](crate::AccelBandwidth) and
+///   [`GyroBandwidth`](crate::GyroBandwidth).
+/// - the MPU6050 has none of these, nor an accelerometer DLPF, gyroscope
+///   standby or DLPF bypass, FIFO stop-on-full mode or a self-test the
+///   driver can evaluate.
+///
+/// Using a feature the chip lacks fails with [`Error::InvalidConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Model {
+    #[default]
+    Mpu6886,
+    Mpu6500,
+    Mpu6050,
+}
+
+impl Model {
+    /// Returns the WHO_AM_I value of the chip.
+    pub fn who_am_i(self) -> u8 {
+        match self {
+            Model::Mpu6886 => 0x19,
+            Model::Mpu6500 => 0x70,
+            Model::Mpu6050 => 0x68,
+        }
+    }
+
+    /// Returns the chip reporting the given WHO_AM_I value, if known.
+    pub fn from_who_am_i(chip_id: u8) -> Option<Self> {
+        [Model::Mpu6886, Model::Mpu6500, Model::Mpu6050]
+            .into_iter()
+            .find(|model| model.who_am_i() == chip_id)
+    }
+
+    /// Converts a raw temperature reading to degrees Celsius.
+    pub(crate) fn scale_temperature(self, raw: i16) -> f32 {
+        match self {
+            Model::Mpu6886 => raw as f32 / 326.8 + 25.0,
+            Model::Mpu6500 => raw as f32 / 333.87 + 21.0,
+            Model::Mpu6050 => raw as f32 / 340.0 + 36.53,
+        }
+    }
+
+    /// Returns the high byte registers of the X, Y and Z accelerometer
+    /// offsets.
+    pub(crate) fn accel_offset_regs(self) -> [u8; 3] {
+        match self {
+            Model::Mpu6886 | Model::Mpu6500 => [
+                registers::XA_OFFSET_H,
+                registers::YA_OFFSET_H,
+                registers::ZA_OFFSET_H,
+            ],
+            // XA/YA/ZA_OFFS_H on the MPU6050
+            Model::Mpu6050 => [0x06, 0x08, 0x0A],
+        }
+    }
+
+    pub(crate) fn supports(self, feature: Feature) -> bool {
+        match self {
+            Model::Mpu6886 => true,
+            Model::Mpu6500 => !matches!(
+                feature,
+                Feature::AccelAveraging
+                    | Feature::GyroLowPower
+                    | Feature::FifoWatermark
+                    | Feature::PerAxisWakeOnMotion
+            ),
+            Model::Mpu6050 => false,
+        }
+    }
+
+    /// Fails with [`Error::InvalidConfig`] if the chip lacks `feature`.
+    pub(crate) fn require<E>(self, feature: Feature) -> Result<(), Error<E>> {
+        if self.supports(feature) {
+            Ok(())
+        } else {
+            Err(Error::InvalidConfig(feature.missing()))
+        }
+    }
+
+    /// Returns the FIFO_EN bits of the accelerometer, the gyroscope and the
+    /// temperature sensor.
+    pub(crate) fn fifo_en_bits(self) -> (u8, u8, u8) {
+        match self {
+            // temperature is pushed along with any other source
+            Model::Mpu6886 => (fifo_en::ACCEL_FIFO_EN, fifo_en::GYRO_FIFO_EN, 0),
+            // ACCEL_FIFO_EN, XG/YG/ZG_FIFO_EN, TEMP_FIFO_EN
+            Model::Mpu6500 | Model::Mpu6050 => (0b00001000, 0b01110000, 0b10000000),
+        }
+    }
+
+    /// Returns the INT_ENABLE and INT_STATUS wake-on-motion bits.
+    pub(crate) fn wom_int_bits(self) -> u8 {
+        match self {
+            Model::Mpu6886 => registers::int_enable::WOM_INT_EN,
+            // WOM_EN, one bit for all axes
+            Model::Mpu6500 => 0b01000000,
+            Model::Mpu6050 => 0,
+        }
+    }
+
+    /// Returns the register of the X gyroscope self-test trim code.
+    pub(crate) fn self_test_gyro_reg(self) -> u8 {
+        match self {
+            // SELF_TEST_X_GYRO on the MPU6500
+            Model::Mpu6500 => 0x00,
+            Model::Mpu6886 | Model::Mpu6050 => registers::SELF_TEST_X_GYRO,
+        }
+    }
+}
+
+/// Features only some models have, see [`Model::require`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Feature {
+    /// ACCEL_CONFIG2, the accelerometer DLPF.
+    AccelBandwidth,
+    /// DEC2_CFG, accelerometer averaging in low power mode.
+    AccelAveraging,
+    /// LP_MODE_CFG, the gyroscope low power mode.
+    GyroLowPower,
+    /// FCHOICE_B, bypassing the gyroscope DLPF.
+    GyroDlpfBypass,
+    /// GYRO_STANDBY in PWR_MGMT_1.
+    GyroStandby,
+    /// FIFO_MODE, stopping the FIFO when full.
+    FifoMode,
+    /// FIFO_WM_TH and FIFO_WM_INT_STATUS.
+    FifoWatermark,
+    /// Wake-on-motion with one threshold for all axes.
+    WakeOnMotion,
+    /// Per-axis wake-on-motion thresholds and flags, and the all-axes mode.
+    PerAxisWakeOnMotion,
+    /// Self-test trim codes following the MPU6500 formula.
+    SelfTest,
+}
+
+impl Feature {
+    fn missing(self) -> &'static str {
+        match self {
+            Feature::AccelBandwidth => "the chip has no accelerometer DLPF",
+            Feature::AccelAveraging => "the chip has no accelerometer averaging",
+            Feature::GyroLowPower => "the chip has no gyroscope low power mode",
+            Feature::GyroDlpfBypass => "the chip can't bypass the gyroscope DLPF",
+            Feature::GyroStandby => "the chip has no gyroscope standby",
+            Feature::FifoMode => "the chip has no FIFO stop-on-full mode",
+            Feature::FifoWatermark => "the chip has no FIFO watermark",
+            Feature::WakeOnMotion => "the chip has no wake-on-motion",
+            Feature::PerAxisWakeOnMotion => "the chip has no per-axis wake-on-motion",
+            Feature::SelfTest => "the chip's self-test isn't supported",
+        }
+    }
+}
+
+/// Chip identity and loaded configuration, returned by
+/// [`Mpu6886::identify`] for logging at boot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    pub chip_id: u8,
+    /// Chip reporting `chip_id`, `None` if unknown.
+    pub model: Option<Model>,
+    /// Bus address, `None` on buses without one.
+    pub address: Option<u8>,
+    pub accel_range: AccelScaleRange,
+    pub gyro_range: GyroScaleRange,
+    pub clock_source: ClockSource,
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads the chip ID and current configuration.
+    ///
+    /// Doesn't check the chip ID, use it after [`Mpu6886::init`] or to
+    /// log what answered when it failed.
+    pub fn identify(&mut self) -> Result<DeviceInfo, Error<DI::Error>> {
+        let chip_id = self.who_am_i()?;
+        Ok(DeviceInfo {
+            chip_id,
+            model: Model::from_who_am_i(chip_id),
+            address: self.iface.address(),
+            accel_range: self.get_accel_scale_range()?,
+            gyro_range: self.get_gyro_scale_range()?,
+            clock_source: self.get_clock_source()?,
+        })
+    }
+}
@@ -0,0 +1,170 @@
+//! Wake-on-motion implementation.
+
+use crate::model::Feature;
+use crate::registers::accel_intel_ctrl::{ACCEL_INTEL_EN, ACCEL_INTEL_MODE, WOM_TH_MODE};
+use crate::registers::pwr_mgmt_1::{CYCLE, GYRO_STANDBY, SLEEP};
+use crate::registers::pwr_mgmt_2::{STBY_A, STBY_G};
+use crate::{registers, AccelBandwidth, Error, Interface, Model, Mpu6886};
+
+/// WOM_THR, the single wake-on-motion threshold of the MPU6500.
+const MPU6500_WOM_THR: u8 = 0x1F;
+/// LP_ACCEL_ODR, the MPU6500 cycle mode wake-up rate.
+const MPU6500_LP_ACCEL_ODR: u8 = 0x1E;
+/// Wake-up rates selected by LP_ACCEL_ODR, in mHz.
+const MPU6500_LP_ACCEL_RATES_MHZ: [u32; 12] = [
+    240, 490, 980, 1950, 3910, 7810, 15630, 31250, 62500, 125000, 250000, 500000,
+];
+
+/// Which axes must exceed the threshold to trigger wake-on-motion.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WakeOnMotionMode {
+    /// Any axis exceeding its threshold triggers.
+    #[default]
+    AnyAxis,
+    /// All axes must exceed their thresholds.
+    AllAxes,
+}
+
+/// Converts a threshold in mg to the register value, 4 mg per LSB.
+fn threshold_from_mg(threshold_mg: u16) -> u8 {
+    (threshold_mg / 4).min(255) as u8
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Sets the wake-on-motion threshold of all axes, in mg.
+    ///
+    /// The resolution is 4 mg, the max value is 1020 mg.
+    pub fn set_wake_on_motion_threshold(
+        &mut self,
+        threshold_mg: u16,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        if self.model.supports(Feature::PerAxisWakeOnMotion) {
+            self.set_wake_on_motion_thresholds((threshold_mg, threshold_mg, threshold_mg))
+        } else {
+            self.write_u8(MPU6500_WOM_THR, threshold_from_mg(threshold_mg))
+        }
+    }
+
+    /// Sets the wake-on-motion thresholds, (X, Y, Z), in mg.
+    ///
+    /// The resolution is 4 mg, the max value is 1020 mg. Only the MPU6886
+    /// has per-axis thresholds.
+    pub fn set_wake_on_motion_thresholds(
+        &mut self,
+        thresholds_mg: (u16, u16, u16),
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::PerAxisWakeOnMotion)?;
+        self.write_u8(
+            registers::ACCEL_WOM_X_THR,
+            threshold_from_mg(thresholds_mg.0),
+        )?;
+        self.write_u8(
+            registers::ACCEL_WOM_Y_THR,
+            threshold_from_mg(thresholds_mg.1),
+        )?;
+        self.write_u8(
+            registers::ACCEL_WOM_Z_THR,
+            threshold_from_mg(thresholds_mg.2),
+        )
+    }
+
+    /// Returns the wake-on-motion thresholds, (X, Y, Z), in mg.
+    pub fn get_wake_on_motion_thresholds(&mut self) -> Result<(u16, u16, u16), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        if !self.model.supports(Feature::PerAxisWakeOnMotion) {
+            let threshold = self.read_u8(MPU6500_WOM_THR)? as u16 * 4;
+            return Ok((threshold, threshold, threshold));
+        }
+        let mut buf: [u8; 3] = [0; 3];
+        self.read_buf(registers::ACCEL_WOM_X_THR, &mut buf)?;
+        Ok((buf[0] as u16 * 4, buf[1] as u16 * 4, buf[2] as u16 * 4))
+    }
+
+    /// Enables the wake-on-motion logic.
+    ///
+    /// Every accelerometer sample is compared with the previous one.
+    /// [`WakeOnMotionMode::AllAxes`] needs an MPU6886.
+    pub fn enable_wake_on_motion(
+        &mut self,
+        mode: WakeOnMotionMode,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        if mode == WakeOnMotionMode::AllAxes {
+            self.model.require(Feature::PerAxisWakeOnMotion)?;
+        }
+        let original_value = self.read_u8(registers::ACCEL_INTEL_CTRL)?;
+        let mut new_value = original_value & !(ACCEL_INTEL_EN | ACCEL_INTEL_MODE | WOM_TH_MODE)
+            | ACCEL_INTEL_EN
+            | ACCEL_INTEL_MODE;
+        if mode == WakeOnMotionMode::AllAxes {
+            new_value |= WOM_TH_MODE;
+        }
+        self.write_u8(registers::ACCEL_INTEL_CTRL, new_value)
+    }
+
+    pub fn disable_wake_on_motion(&mut self) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        let original_value = self.read_u8(registers::ACCEL_INTEL_CTRL)?;
+        let new_value = original_value & !(ACCEL_INTEL_EN | ACCEL_INTEL_MODE);
+        self.write_u8(registers::ACCEL_INTEL_CTRL, new_value)
+    }
+
+    /// Fires the INT pin when motion is detected on any axis.
+    pub fn enable_wake_on_motion_interrupt(&mut self) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        let original_value = self.read_u8(registers::INT_ENABLE)?;
+        let new_value = original_value | self.model.wom_int_bits();
+        self.write_u8(registers::INT_ENABLE, new_value)
+    }
+
+    pub fn disable_wake_on_motion_interrupt(&mut self) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        let original_value = self.read_u8(registers::INT_ENABLE)?;
+        let new_value = original_value & !self.model.wom_int_bits();
+        self.write_u8(registers::INT_ENABLE, new_value)
+    }
+
+    /// Puts the chip into accelerometer low power mode with wake-on-motion.
+    ///
+    /// Follows the datasheet sequence: the gyroscope is turned off, the
+    /// accelerometer runs in cycle mode at `odr_hz` (4 to 1000 Hz) and the
+    /// INT pin fires when any axis moves more than `threshold_mg`. The
+    /// MPU6500 wakes up at the next lower LP_ACCEL_ODR rate instead, 0.24 to
+    /// 500 Hz.
+    pub fn configure_wake_on_motion(
+        &mut self,
+        threshold_mg: u16,
+        odr_hz: u16,
+    ) -> Result<(), Error<DI::Error>> {
+        self.model.require(Feature::WakeOnMotion)?;
+        // wake up, leave cycle mode and gyro standby
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value & !(SLEEP | CYCLE | GYRO_STANDBY);
+        self.write_u8(registers::PWR_MGMT_1, new_value)?;
+        // accelerometer on, gyro off
+        let original_value = self.read_u8(registers::PWR_MGMT_2)?;
+        let new_value = original_value & !(STBY_A | STBY_G) | STBY_G;
+        self.write_u8(registers::PWR_MGMT_2, new_value)?;
+        self.set_accel_bandwidth(AccelBandwidth::Hz218)?;
+        self.enable_wake_on_motion_interrupt()?;
+        self.set_wake_on_motion_threshold(threshold_mg)?;
+        self.enable_wake_on_motion(WakeOnMotionMode::AnyAxis)?;
+        if self.model == Model::Mpu6500 {
+            let rate_mhz = odr_hz as u32 * 1000;
+            let code = MPU6500_LP_ACCEL_RATES_MHZ
+                .iter()
+                .rposition(|&rate| rate <= rate_mhz)
+                .unwrap_or(0);
+            self.write_u8(MPU6500_LP_ACCEL_ODR, code as u8)?;
+        } else {
+            self.set_output_data_rate(odr_hz)?;
+        }
+        // enter cycle mode
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = original_value | CYCLE;
+        self.write_u8(registers::PWR_MGMT_1, new_value)
+    }
+}
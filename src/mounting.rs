@@ -0,0 +1,193 @@
+//! Mounting orientation implementation.
+
+use crate::{Interface, Mpu6886};
+
+use core::ops::Neg;
+
+/// Sensor axis, with its sign.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Axis {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Axis {
+    fn pick<T: Copy + Neg<Output = T>>(self, (x, y, z): (T, T, T)) -> T {
+        match self {
+            Axis::PosX => x,
+            Axis::NegX => -x,
+            Axis::PosY => y,
+            Axis::NegY => -y,
+            Axis::PosZ => z,
+            Axis::NegZ => -z,
+        }
+    }
+}
+
+/// Which sensor axis each output axis is taken from, to report readings in
+/// the frame of the board instead of the chip.
+///
+/// E.g. `AxisMap { x: Axis::NegY, y: Axis::PosX, z: Axis::PosZ }` reports
+/// the negated chip Y axis as X and the chip X axis as Y.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisMap {
+    pub x: Axis,
+    pub y: Axis,
+    pub z: Axis,
+}
+
+impl Default for AxisMap {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl AxisMap {
+    /// Board and chip axes are the same.
+    pub const IDENTITY: Self = Self {
+        x: Axis::PosX,
+        y: Axis::PosY,
+        z: Axis::PosZ,
+    };
+
+    /// Maps a sensor frame vector, (X, Y, Z), into the board frame.
+    pub fn apply<T: Copy + Neg<Output = T>>(&self, value: (T, T, T)) -> (T, T, T) {
+        (self.x.pick(value), self.y.pick(value), self.z.pick(value))
+    }
+}
+
+/// Row-major 3×3 matrix without effect.
+pub const IDENTITY_MATRIX: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Fractional bits of the fixed-point alignment matrix.
+const ALIGNMENT_FRACTION_BITS: u32 = 14;
+
+/// Chip to board transformation of converted readings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Mounting {
+    alignment: [[f32; 3]; 3],
+    /// `alignment` in fixed point, for the integer readings.
+    alignment_fixed: [[i32; 3]; 3],
+    pub(crate) axis_map: AxisMap,
+}
+
+impl Default for Mounting {
+    fn default() -> Self {
+        let one = 1 << ALIGNMENT_FRACTION_BITS;
+        Self {
+            alignment: IDENTITY_MATRIX,
+            alignment_fixed: [[one, 0, 0], [0, one, 0], [0, 0, one]],
+            axis_map: AxisMap::IDENTITY,
+        }
+    }
+}
+
+impl Mounting {
+    pub(crate) fn alignment(&self) -> [[f32; 3]; 3] {
+        self.alignment
+    }
+
+    pub(crate) fn set_alignment(&mut self, matrix: [[f32; 3]; 3]) {
+        let scale = (1 << ALIGNMENT_FRACTION_BITS) as f32;
+        // rounded to nearest, saturating
+        let fixed = |value: f32| (value * scale + 0.5f32.copysign(value)) as i32;
+        self.alignment = matrix;
+        self.alignment_fixed = matrix.map(|row| row.map(fixed));
+    }
+
+    /// Applies the alignment matrix, then the axis mapping.
+    pub(crate) fn apply(&self, (x, y, z): (f32, f32, f32)) -> (f32, f32, f32) {
+        let [r0, r1, r2] = self.alignment;
+        let row = |r: [f32; 3]| r[0] * x + r[1] * y + r[2] * z;
+        self.axis_map.apply((row(r0), row(r1), row(r2)))
+    }
+
+    /// Applies the alignment matrix in fixed point, rounded to the nearest
+    /// unit, then the axis mapping.
+    pub(crate) fn apply_fixed(&self, (x, y, z): (i32, i32, i32)) -> (i32, i32, i32) {
+        let [r0, r1, r2] = self.alignment_fixed;
+        let half = 1i64 << (ALIGNMENT_FRACTION_BITS - 1);
+        let row = |r: [i32; 3]| {
+            let sum = r[0] as i64 * x as i64 + r[1] as i64 * y as i64 + r[2] as i64 * z as i64;
+            ((sum + half) >> ALIGNMENT_FRACTION_BITS).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+        };
+        self.axis_map.apply((row(r0), row(r1), row(r2)))
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Sets how the chip is mounted. Applies to all converted readings, raw
+    /// readings stay in the chip frame.
+    pub fn set_axis_mapping(&mut self, axis_map: AxisMap) {
+        self.mounting.axis_map = axis_map;
+    }
+
+    pub fn axis_mapping(&self) -> AxisMap {
+        self.mounting.axis_map
+    }
+
+    /// Sets a row-major matrix correcting small misalignment, applied to
+    /// converted accelerometer and gyroscope readings in the chip frame,
+    /// before the axis mapping.
+    ///
+    /// The integer mg and mdps readings apply it in fixed point, with a
+    /// resolution of 1/16384 and results rounded to the nearest mg or mdps.
+    pub fn set_alignment_matrix(&mut self, matrix: [[f32; 3]; 3]) {
+        self.mounting.set_alignment(matrix);
+    }
+
+    pub fn alignment_matrix(&self) -> [[f32; 3]; 3] {
+        self.mounting.alignment()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILTED: [[f32; 3]; 3] = [[0.8, -0.6, 0.0], [0.6, 0.8, 0.0], [0.0, 0.0, 1.0]];
+
+    #[test]
+    fn identity_mounting_keeps_values() {
+        let mounting = Mounting::default();
+        assert_eq!(
+            mounting.apply_fixed((1000, -2, 2_000_000)),
+            (1000, -2, 2_000_000)
+        );
+    }
+
+    #[test]
+    fn fixed_point_matches_float_alignment() {
+        let mut mounting = Mounting::default();
+        mounting.set_alignment(TILTED);
+        mounting.axis_map = AxisMap {
+            x: Axis::NegY,
+            y: Axis::PosX,
+            z: Axis::PosZ,
+        };
+        for value in [(1000, 0, 0), (123, -456, 789), (2_000_000, -1_500_000, 7)] {
+            let fixed = mounting.apply_fixed(value);
+            let float = mounting.apply((value.0 as f32, value.1 as f32, value.2 as f32));
+            // half an LSB of each coefficient, plus the rounding
+            let largest = value.0.abs().max(value.1.abs()).max(value.2.abs()) as f32;
+            let close = |a: i32, b: f32| (a as f32 - b).abs() <= 1.0 + largest * 3.0 / 32768.0;
+            assert!(close(fixed.0, float.0) && close(fixed.1, float.1) && close(fixed.2, float.2));
+        }
+    }
+
+    #[test]
+    fn fixed_point_rounds_to_nearest() {
+        let mut mounting = Mounting::default();
+        mounting.set_alignment([[0.5, 0.0, 0.0], [-0.5, 0.0, 0.0], [0.25, 0.0, 0.0]]);
+        // 1.5 and -1.5 round up, 0.75 rounds to 1
+        assert_eq!(mounting.apply_fixed((3, 0, 0)), (2, -1, 1));
+    }
+}
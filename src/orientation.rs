@@ -0,0 +1,82 @@
+//! Lightweight pitch/roll estimation.
+
+use crate::{Error, Interface, Measurement, Mpu6886};
+
+use libm::{atan2f, sqrtf};
+
+/// Returns (roll, pitch) in radians of the gravity vector `accel`.
+pub(crate) fn accel_tilt((ax, ay, az): (f32, f32, f32)) -> (f32, f32) {
+    let roll = atan2f(ay, az);
+    let pitch = atan2f(-ax, sqrtf(ay * ay + az * az));
+    (roll, pitch)
+}
+
+/// Complementary filter estimating roll and pitch.
+///
+/// Integrates the gyroscope and pulls the result towards the accelerometer
+/// tilt with weight `1 - alpha`. Yaw isn't observable without a magnetometer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ComplementaryFilter {
+    alpha: f32,
+    angles: Option<(f32, f32)>,
+}
+
+impl Default for ComplementaryFilter {
+    fn default() -> Self {
+        Self::new(0.98)
+    }
+}
+
+impl ComplementaryFilter {
+    /// Creates a filter trusting the gyroscope with weight `alpha`, e.g. 0.98.
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha,
+            angles: None,
+        }
+    }
+
+    /// Returns the last (roll, pitch) estimate in radians.
+    pub fn angles(&self) -> Option<(f32, f32)> {
+        self.angles
+    }
+
+    /// Feeds one sample, `gyro` in rad/s, `accel` in any unit, `dt` in
+    /// seconds since the previous one. Returns (roll, pitch) in radians.
+    ///
+    /// The first sample starts from the accelerometer tilt.
+    pub fn update(&mut self, gyro: (f32, f32, f32), accel: (f32, f32, f32), dt: f32) -> (f32, f32) {
+        let (accel_roll, accel_pitch) = accel_tilt(accel);
+        let angles = match self.angles {
+            None => (accel_roll, accel_pitch),
+            Some((roll, pitch)) => (
+                self.alpha * (roll + gyro.0 * dt) + (1.0 - self.alpha) * accel_roll,
+                self.alpha * (pitch + gyro.1 * dt) + (1.0 - self.alpha) * accel_pitch,
+            ),
+        };
+        self.angles = Some(angles);
+        angles
+    }
+
+    /// Restarts the estimation from the next accelerometer sample.
+    pub fn reset(&mut self) {
+        self.angles = None;
+    }
+}
+
+impl Measurement {
+    /// Returns (roll, pitch) in radians from the acceleration alone.
+    pub fn tilt(&self) -> (f32, f32) {
+        accel_tilt(self.accel)
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Returns (roll, pitch) in radians from one accelerometer sample.
+    ///
+    /// Only accurate while the sensor isn't accelerating, e.g. for leveling.
+    pub fn tilt(&mut self) -> Result<(f32, f32), Error<DI::Error>> {
+        Ok(accel_tilt(self.acceleration()?))
+    }
+}
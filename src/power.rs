@@ -0,0 +1,292 @@
+//! Power mode implementation.
+
+use crate::model::Feature;
+use crate::{registers, Error, Interface, Mpu6886};
+
+/// Power modes of the chip, as listed in the datasheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerMode {
+    /// Everything off, registers are retained.
+    Sleep,
+    /// Gyroscope drive on, sensing off, accelerometer off. Wakes up faster
+    /// than sleep mode.
+    Standby,
+    /// Accelerometer and gyroscope in low noise mode.
+    SixAxisLowNoise,
+    /// Accelerometer in duty-cycled low power mode, gyroscope off.
+    AccelLowPower,
+    /// Gyroscope in low noise mode, accelerometer off.
+    GyroOnly,
+    /// Accelerometer in low noise mode, gyroscope off.
+    AccelOnly,
+}
+
+impl PowerMode {
+    /// Encodes the mode into PWR_MGMT_1 and PWR_MGMT_2 register values,
+    /// PWR_MGMT_2 is `None` if it's left as is.
+    pub(crate) fn apply_to_regs(self, pwr_mgmt_1: u8, pwr_mgmt_2: u8) -> (u8, Option<u8>) {
+        // (CYCLE, GYRO_STANDBY, accel on, gyro on)
+        let (cycle, standby, accel, gyro) = match self {
+            PowerMode::Sleep => return (pwr_mgmt_1 | registers::pwr_mgmt_1::SLEEP, None),
+            PowerMode::Standby => (false, true, false, true),
+            PowerMode::SixAxisLowNoise => (false, false, true, true),
+            PowerMode::AccelLowPower => (true, false, true, false),
+            PowerMode::GyroOnly => (false, false, false, true),
+            PowerMode::AccelOnly => (false, false, true, false),
+        };
+        let mut new_pwr_mgmt_1 = pwr_mgmt_1
+            & !(registers::pwr_mgmt_1::SLEEP
+                | registers::pwr_mgmt_1::CYCLE
+                | registers::pwr_mgmt_1::GYRO_STANDBY);
+        if cycle {
+            new_pwr_mgmt_1 |= registers::pwr_mgmt_1::CYCLE;
+        }
+        if standby {
+            new_pwr_mgmt_1 |= registers::pwr_mgmt_1::GYRO_STANDBY;
+        }
+        let mut new_pwr_mgmt_2 =
+            pwr_mgmt_2 & !(registers::pwr_mgmt_2::STBY_A | registers::pwr_mgmt_2::STBY_G);
+        if !accel {
+            new_pwr_mgmt_2 |= registers::pwr_mgmt_2::STBY_A;
+        }
+        if !gyro {
+            new_pwr_mgmt_2 |= registers::pwr_mgmt_2::STBY_G;
+        }
+        (new_pwr_mgmt_1, Some(new_pwr_mgmt_2))
+    }
+
+    /// Decodes the mode from PWR_MGMT_1 and PWR_MGMT_2 register values,
+    /// `None` for states not covered by [`PowerMode`].
+    pub(crate) fn from_regs(pwr_mgmt_1: u8, pwr_mgmt_2: u8) -> Option<Self> {
+        if pwr_mgmt_1 & registers::pwr_mgmt_1::SLEEP != 0 {
+            return Some(PowerMode::Sleep);
+        }
+        let cycle = pwr_mgmt_1 & registers::pwr_mgmt_1::CYCLE != 0;
+        let standby = pwr_mgmt_1 & registers::pwr_mgmt_1::GYRO_STANDBY != 0;
+        let accel = match pwr_mgmt_2 & registers::pwr_mgmt_2::STBY_A {
+            0 => true,
+            registers::pwr_mgmt_2::STBY_A => false,
+            _ => return None,
+        };
+        let gyro = match pwr_mgmt_2 & registers::pwr_mgmt_2::STBY_G {
+            0 => true,
+            registers::pwr_mgmt_2::STBY_G => false,
+            _ => return None,
+        };
+        match (cycle, standby, accel, gyro) {
+            (false, true, false, true) => Some(PowerMode::Standby),
+            (false, false, true, true) => Some(PowerMode::SixAxisLowNoise),
+            (true, false, true, false) => Some(PowerMode::AccelLowPower),
+            (false, false, false, true) => Some(PowerMode::GyroOnly),
+            (false, false, true, false) => Some(PowerMode::AccelOnly),
+            _ => None,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Sensor axes to keep powered, the inverse of the PWR_MGMT_2 standby
+    /// bits.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SensorAxes: u8 {
+        const ACCEL_X = registers::pwr_mgmt_2::STBY_XA;
+        const ACCEL_Y = registers::pwr_mgmt_2::STBY_YA;
+        const ACCEL_Z = registers::pwr_mgmt_2::STBY_ZA;
+        const GYRO_X = registers::pwr_mgmt_2::STBY_XG;
+        const GYRO_Y = registers::pwr_mgmt_2::STBY_YG;
+        const GYRO_Z = registers::pwr_mgmt_2::STBY_ZG;
+        const ACCEL = Self::ACCEL_X.bits() | Self::ACCEL_Y.bits() | Self::ACCEL_Z.bits();
+        const GYRO = Self::GYRO_X.bits() | Self::GYRO_Y.bits() | Self::GYRO_Z.bits();
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SensorAxes {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "SensorAxes({=u8:#b})", self.bits())
+    }
+}
+
+impl SensorAxes {
+    /// Decodes the powered axes from a PWR_MGMT_2 register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        Self::from_bits_truncate(!raw_value)
+    }
+
+    /// Encodes the standby bits into a PWR_MGMT_2 register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        original_value & !(registers::pwr_mgmt_2::STBY_A | registers::pwr_mgmt_2::STBY_G)
+            | !self.bits() & Self::all().bits()
+    }
+}
+
+/// Clock source selected by CLKSEL.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClockSource {
+    /// Internal 20 MHz oscillator.
+    #[default]
+    Internal,
+    /// Best available clock, the PLL once the gyroscope runs, the internal
+    /// oscillator otherwise.
+    AutoSelect,
+    /// Stops the clock and keeps timing generators in reset.
+    Stopped,
+}
+
+impl ClockSource {
+    /// Decodes CLKSEL from a PWR_MGMT_1 register value.
+    pub(crate) fn from_reg(raw_value: u8) -> Self {
+        match raw_value & registers::pwr_mgmt_1::CLKSEL {
+            0 | 6 => ClockSource::Internal,
+            7 => ClockSource::Stopped,
+            _ => ClockSource::AutoSelect,
+        }
+    }
+
+    /// Encodes CLKSEL into a PWR_MGMT_1 register value.
+    pub(crate) fn apply_to_reg(self, original_value: u8) -> u8 {
+        let clksel = match self {
+            ClockSource::Internal => 0,
+            ClockSource::AutoSelect => 1,
+            ClockSource::Stopped => 7,
+        };
+        original_value & !registers::pwr_mgmt_1::CLKSEL | clksel
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    pub fn set_clock_source(&mut self, source: ClockSource) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+        let new_value = source.apply_to_reg(original_value);
+        self.write_u8(registers::PWR_MGMT_1, new_value)
+    }
+
+    pub fn get_clock_source(&mut self) -> Result<ClockSource, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::PWR_MGMT_1)?;
+        Ok(ClockSource::from_reg(raw_value))
+    }
+
+    /// Powers exactly the given axes, the others go to standby.
+    pub fn set_enabled_axes(&mut self, axes: SensorAxes) -> Result<(), Error<DI::Error>> {
+        let original_value = self.read_u8(registers::PWR_MGMT_2)?;
+        let new_value = axes.apply_to_reg(original_value);
+        self.write_u8(registers::PWR_MGMT_2, new_value)
+    }
+
+    pub fn get_enabled_axes(&mut self) -> Result<SensorAxes, Error<DI::Error>> {
+        let raw_value = self.read_u8(registers::PWR_MGMT_2)?;
+        Ok(SensorAxes::from_reg(raw_value))
+    }
+
+    /// Puts the given axes into standby, keeping the others as they are.
+    ///
+    /// Each disabled axis saves supply current, e.g. a drop detector only
+    /// needs [`SensorAxes::ACCEL_Z`].
+    pub fn standby_axes(&mut self, axes: SensorAxes) -> Result<(), Error<DI::Error>> {
+        let enabled = self.get_enabled_axes()?;
+        self.set_enabled_axes(enabled - axes)
+    }
+
+    /// Powers the given axes, keeping the others as they are.
+    pub fn enable_axes(&mut self, axes: SensorAxes) -> Result<(), Error<DI::Error>> {
+        let enabled = self.get_enabled_axes()?;
+        self.set_enabled_axes(enabled | axes)
+    }
+
+    /// Returns the axes in standby, read from the chip.
+    pub fn get_standby_axes(&mut self) -> Result<SensorAxes, Error<DI::Error>> {
+        Ok(self.get_enabled_axes()?.complement())
+    }
+
+    /// Switches the chip into the given power mode.
+    ///
+    /// Writes SLEEP, CYCLE and GYRO_STANDBY together with the sensor enable
+    /// bits, so the chip always ends up in a consistent state.
+    pub fn set_power_mode(&mut self, mode: PowerMode) -> Result<(), Error<DI::Error>> {
+        if mode == PowerMode::Standby {
+            self.model.require(Feature::GyroStandby)?;
+        }
+        let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1)?;
+        let pwr_mgmt_2 = self.read_u8(registers::PWR_MGMT_2)?;
+        let (new_pwr_mgmt_1, new_pwr_mgmt_2) = mode.apply_to_regs(pwr_mgmt_1, pwr_mgmt_2);
+        self.write_u8(registers::PWR_MGMT_1, new_pwr_mgmt_1)?;
+        match new_pwr_mgmt_2 {
+            Some(value) => self.write_u8(registers::PWR_MGMT_2, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the current power mode.
+    ///
+    /// Returns `None` if the chip is in a state not covered by [`PowerMode`],
+    /// e.g. with single axes disabled.
+    pub fn get_power_mode(&mut self) -> Result<Option<PowerMode>, Error<DI::Error>> {
+        let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1)?;
+        let pwr_mgmt_2 = self.read_u8(registers::PWR_MGMT_2)?;
+        Ok(PowerMode::from_regs(pwr_mgmt_1, pwr_mgmt_2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [PowerMode; 6] = [
+        PowerMode::Sleep,
+        PowerMode::Standby,
+        PowerMode::SixAxisLowNoise,
+        PowerMode::AccelLowPower,
+        PowerMode::GyroOnly,
+        PowerMode::AccelOnly,
+    ];
+
+    #[test]
+    fn modes_round_trip_through_registers() {
+        // clock source and per-axis standby bits from the reset state
+        for mode in MODES {
+            let (pwr_mgmt_1, pwr_mgmt_2) = mode.apply_to_regs(0b01000001, 0);
+            let decoded = PowerMode::from_regs(pwr_mgmt_1, pwr_mgmt_2.unwrap_or(0));
+            assert_eq!(decoded, Some(mode));
+        }
+    }
+
+    #[test]
+    fn mode_encoding() {
+        assert_eq!(
+            PowerMode::SixAxisLowNoise.apply_to_regs(0b01100001, 0b00111111),
+            (0b00000001, Some(0))
+        );
+        assert_eq!(
+            PowerMode::AccelLowPower.apply_to_regs(0b01000001, 0),
+            (0b00100001, Some(0b00000111))
+        );
+        assert_eq!(
+            PowerMode::Standby.apply_to_regs(0b00000001, 0),
+            (0b00010001, Some(0b00111000))
+        );
+        assert_eq!(
+            PowerMode::Sleep.apply_to_regs(0b00000001, 0),
+            (0b01000001, None)
+        );
+    }
+
+    #[test]
+    fn partial_standby_is_not_a_mode() {
+        assert_eq!(PowerMode::from_regs(0, 0b00000001), None);
+        assert_eq!(PowerMode::from_regs(0, 0b00100000), None);
+        // cycle mode with the gyroscope on
+        assert_eq!(PowerMode::from_regs(0b00100000, 0), None);
+    }
+
+    #[test]
+    fn sleep_overrides_other_bits() {
+        assert_eq!(
+            PowerMode::from_regs(0b01110000, 0b00000001),
+            Some(PowerMode::Sleep)
+        );
+    }
+}
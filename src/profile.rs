@@ -0,0 +1,56 @@
+//! Configuration presets.
+
+use crate::{
+    AccelBandwidth, AccelScaleRange, ClockSource, Error, FifoConfig, GyroBandwidth, GyroScaleRange,
+    Interface, Mpu6886, Mpu6886Config, PowerMode,
+};
+
+/// Ready-made configurations for common applications.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Profile {
+    /// Orientation tracking and sensor fusion: 200 Hz output data rate,
+    /// ±2000 dps, ±8 g, 41 Hz gyro and 45 Hz accelerometer DLPF, the FIFO
+    /// collects both sensors and the PLL clock is used.
+    MotionTracking,
+    /// Waiting for movement: the accelerometer runs in low power mode at
+    /// `odr_hz` (4 to 1000 Hz), the gyroscope, temperature sensor and FIFO
+    /// are off and the INT pin fires on motion above `threshold_mg`.
+    LowPowerWakeup { threshold_mg: u16, odr_hz: u16 },
+}
+
+impl Profile {
+    /// Returns the configuration written by [`Mpu6886::apply_profile`].
+    pub fn config(self) -> Mpu6886Config {
+        match self {
+            Profile::MotionTracking => Mpu6886Config::reset_state()
+                .with_accel_range(AccelScaleRange::Range8g)
+                .with_gyro_range(GyroScaleRange::Range2000Dps)
+                .with_accel_bandwidth(AccelBandwidth::Hz45)
+                .with_gyro_bandwidth(GyroBandwidth::Hz41)
+                // 1 kHz internal rate / (1 + 4)
+                .with_sample_rate_divider(4)
+                .with_clock_source(ClockSource::AutoSelect)
+                .with_fifo(Some(FifoConfig::default())),
+            Profile::LowPowerWakeup { .. } => Mpu6886Config::reset_state()
+                .with_accel_bandwidth(AccelBandwidth::Hz218)
+                .with_temperature_sensor(false),
+        }
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Applies a preset and switches to the power mode it needs.
+    pub fn apply_profile(&mut self, profile: Profile) -> Result<(), Error<DI::Error>> {
+        self.apply_config(&profile.config())?;
+        match profile {
+            Profile::MotionTracking => self.set_power_mode(PowerMode::SixAxisLowNoise),
+            Profile::LowPowerWakeup {
+                threshold_mg,
+                odr_hz,
+            } => self.configure_wake_on_motion(threshold_mg, odr_hz),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Interrupt-to-task sample queue.
+//!
+//! Call [`Mpu6886Reader::push_sample`] from the data-ready interrupt handler
+//! and pop measurements from the task:
+//!
+//! ```rust,ignore
+//! static mut QUEUE: SampleQueue<16> = SampleQueue::new();
+//! let (mut producer, mut consumer) = unsafe { QUEUE.split() };
+//!
+//! // in the interrupt handler
+//! reader.push_sample(&mut producer)?;
+//!
+//! // in the task
+//! while let Some(measurement) = consumer.dequeue() {}
+//! ```
+
+use heapless::spsc::{Producer, Queue};
+
+use crate::{Error, Interface, Measurement, Mpu6886, Mpu6886Reader};
+
+/// Single-producer single-consumer queue of measurements, holding up to
+/// `N - 1` of them.
+pub type SampleQueue<const N: usize> = Queue<Measurement, N>;
+
+impl<DI: Interface> Mpu6886Reader<DI> {
+    /// Reads one sample and pushes it into the queue.
+    ///
+    /// Returns `false` if the queue is full, the sample is dropped then.
+    pub fn push_sample<const N: usize>(
+        &mut self,
+        producer: &mut Producer<'_, Measurement, N>,
+    ) -> Result<bool, Error<DI::Error>> {
+        let measurement = self.read_all()?;
+        Ok(producer.enqueue(measurement).is_ok())
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Reads one sample and pushes it into the queue.
+    ///
+    /// Returns `false` if the queue is full, the sample is dropped then.
+    pub fn push_sample<const N: usize>(
+        &mut self,
+        producer: &mut Producer<'_, Measurement, N>,
+    ) -> Result<bool, Error<DI::Error>> {
+        let measurement = self.read_all()?;
+        Ok(producer.enqueue(measurement).is_ok())
+    }
+}
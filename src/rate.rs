@@ -0,0 +1,196 @@
+//! Sample rate implementation.
+
+use crate::model::Feature;
+use crate::{registers, AccelBandwidth, Error, GyroBandwidth, Interface, Mpu6886};
+
+/// Returns the internal sample rate in Hz and whether the sample rate divider
+/// applies, from CONFIG and GYRO_CONFIG register values.
+pub(crate) fn internal_sample_rate(config: u8, gyro_config: u8) -> (u32, bool) {
+    if gyro_config & registers::gyro_config::FCHOICE_B != 0 {
+        // DLPF bypassed via FCHOICE_B
+        (32000, false)
+    } else {
+        match config & registers::config::DLPF_CFG {
+            0 | 7 => (8000, false),
+            _ => (1000, true),
+        }
+    }
+}
+
+/// Output data rates with matching filter settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutputDataRate {
+    Hz1000,
+    Hz500,
+    Hz250,
+    Hz200,
+    Hz100,
+    Hz50,
+    Hz20,
+    Hz10,
+}
+
+impl OutputDataRate {
+    pub fn hz(self) -> u16 {
+        match self {
+            OutputDataRate::Hz1000 => 1000,
+            OutputDataRate::Hz500 => 500,
+            OutputDataRate::Hz250 => 250,
+            OutputDataRate::Hz200 => 200,
+            OutputDataRate::Hz100 => 100,
+            OutputDataRate::Hz50 => 50,
+            OutputDataRate::Hz20 => 20,
+            OutputDataRate::Hz10 => 10,
+        }
+    }
+
+    /// Returns the SMPLRT_DIV value for the 1 kHz internal rate.
+    pub(crate) fn divider(self) -> u8 {
+        (1000 / self.hz() - 1) as u8
+    }
+
+    /// Returns the widest DLPF settings below half the output data rate.
+    ///
+    /// At 10 Hz no setting is below 5 Hz, the narrowest one (5 Hz gyro,
+    /// 5.1 Hz accel) is used.
+    pub(crate) fn bandwidths(self) -> (GyroBandwidth, AccelBandwidth) {
+        match self {
+            OutputDataRate::Hz1000 | OutputDataRate::Hz500 => {
+                (GyroBandwidth::Hz176, AccelBandwidth::Hz218)
+            }
+            OutputDataRate::Hz250 | OutputDataRate::Hz200 => {
+                (GyroBandwidth::Hz92, AccelBandwidth::Hz99)
+            }
+            OutputDataRate::Hz100 => (GyroBandwidth::Hz41, AccelBandwidth::Hz45),
+            OutputDataRate::Hz50 => (GyroBandwidth::Hz20, AccelBandwidth::Hz21),
+            OutputDataRate::Hz20 | OutputDataRate::Hz10 => {
+                (GyroBandwidth::Hz5, AccelBandwidth::Hz5)
+            }
+        }
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Sets the output data rate together with gyroscope and accelerometer
+    /// DLPF settings that support it, the MPU6050 has no accelerometer DLPF.
+    pub fn set_data_rate(&mut self, rate: OutputDataRate) -> Result<(), Error<DI::Error>> {
+        let (gyro_bandwidth, accel_bandwidth) = rate.bandwidths();
+        self.set_gyro_bandwidth(gyro_bandwidth)?;
+        if self.model.supports(Feature::AccelBandwidth) {
+            self.set_accel_bandwidth(accel_bandwidth)?;
+        }
+        self.set_sample_rate_divider(rate.divider())
+    }
+
+    /// Same as [`Mpu6886::output_data_rate`], the exact rate resulting from
+    /// the divider and DLPF settings.
+    pub fn actual_odr(&mut self) -> Result<f32, Error<DI::Error>> {
+        self.output_data_rate()
+    }
+
+    /// Sets SMPLRT_DIV, the output data rate is `internal rate / (1 + divider)`.
+    ///
+    /// The divider only applies when the gyroscope DLPF is enabled with a
+    /// 1 kHz internal rate.
+    pub fn set_sample_rate_divider(&mut self, divider: u8) -> Result<(), Error<DI::Error>> {
+        self.write_u8(registers::SMPLRT_DIV, divider)
+    }
+
+    pub fn get_sample_rate_divider(&mut self) -> Result<u8, Error<DI::Error>> {
+        self.read_u8(registers::SMPLRT_DIV)
+    }
+
+    /// Sets the output data rate as close to `odr_hz` as the divider allows,
+    /// the range is 4 to 1000 Hz.
+    ///
+    /// Returns the effective output data rate, which stays at the internal
+    /// rate while the DLPF setting doesn't support the divider.
+    pub fn set_output_data_rate(&mut self, odr_hz: u16) -> Result<f32, Error<DI::Error>> {
+        let divider = (1000 / odr_hz.clamp(4, 1000)) - 1;
+        self.set_sample_rate_divider(divider as u8)?;
+        self.output_data_rate()
+    }
+
+    /// Returns the effective output data rate in Hz.
+    pub fn output_data_rate(&mut self) -> Result<f32, Error<DI::Error>> {
+        // SMPLRT_DIV, CONFIG, GYRO_CONFIG
+        let mut buf: [u8; 3] = [0; 3];
+        self.read_buf(registers::SMPLRT_DIV, &mut buf)?;
+        let (rate, divider_applies) = internal_sample_rate(buf[1], buf[2]);
+        if divider_applies {
+            Ok(rate as f32 / (1.0 + buf[0] as f32))
+        } else {
+            Ok(rate as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: [OutputDataRate; 8] = [
+        OutputDataRate::Hz1000,
+        OutputDataRate::Hz500,
+        OutputDataRate::Hz250,
+        OutputDataRate::Hz200,
+        OutputDataRate::Hz100,
+        OutputDataRate::Hz50,
+        OutputDataRate::Hz20,
+        OutputDataRate::Hz10,
+    ];
+
+    #[test]
+    fn divider_gives_the_rate() {
+        let dividers = RATES.map(OutputDataRate::divider);
+        assert_eq!(dividers, [0, 1, 3, 4, 9, 19, 49, 99]);
+        for rate in RATES {
+            assert_eq!(1000 / (1 + rate.divider() as u16), rate.hz());
+        }
+    }
+
+    #[test]
+    fn bandwidths_keep_the_divider_usable() {
+        for rate in RATES {
+            let (gyro_bandwidth, _) = rate.bandwidths();
+            let (dlpf_cfg, fchoice_b) = gyro_bandwidth.to_fields();
+            assert_eq!(internal_sample_rate(dlpf_cfg, fchoice_b), (1000, true));
+        }
+    }
+
+    #[test]
+    fn bandwidths_match_the_rate() {
+        assert_eq!(
+            OutputDataRate::Hz1000.bandwidths(),
+            (GyroBandwidth::Hz176, AccelBandwidth::Hz218)
+        );
+        assert_eq!(
+            OutputDataRate::Hz200.bandwidths(),
+            (GyroBandwidth::Hz92, AccelBandwidth::Hz99)
+        );
+        assert_eq!(
+            OutputDataRate::Hz100.bandwidths(),
+            (GyroBandwidth::Hz41, AccelBandwidth::Hz45)
+        );
+        assert_eq!(
+            OutputDataRate::Hz50.bandwidths(),
+            (GyroBandwidth::Hz20, AccelBandwidth::Hz21)
+        );
+        assert_eq!(
+            OutputDataRate::Hz10.bandwidths(),
+            (GyroBandwidth::Hz5, AccelBandwidth::Hz5)
+        );
+    }
+
+    #[test]
+    fn internal_rate_follows_dlpf_settings() {
+        assert_eq!(internal_sample_rate(0, 0), (8000, false));
+        assert_eq!(internal_sample_rate(3, 0), (1000, true));
+        assert_eq!(internal_sample_rate(7, 0), (8000, false));
+        assert_eq!(internal_sample_rate(3, 0b01), (32000, false));
+        // FS_SEL doesn't matter
+        assert_eq!(internal_sample_rate(3, 0b11000), (1000, true));
+    }
+}
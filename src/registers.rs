@@ -0,0 +1,208 @@
+//! MPU6886 register map.
+//!
+//! Register addresses, and field masks of the registers the driver uses, for
+//! use with [`Mpu6886::read_register`](crate::Mpu6886::read_register) and
+//! [`Mpu6886::write_register`](crate::Mpu6886::write_register).
+
+pub const XG_OFFS_TC_H: u8 = 0x04;
+pub const XG_OFFS_TC_L: u8 = 0x05;
+pub const YG_OFFS_TC_H: u8 = 0x07;
+pub const YG_OFFS_TC_L: u8 = 0x08;
+pub const ZG_OFFS_TC_H: u8 = 0x0A;
+pub const ZG_OFFS_TC_L: u8 = 0x0B;
+pub const SELF_TEST_X_ACCEL: u8 = 0x0D;
+pub const SELF_TEST_Y_ACCEL: u8 = 0x0E;
+pub const SELF_TEST_Z_ACCEL: u8 = 0x0F;
+pub const XG_OFFS_USRH: u8 = 0x13;
+pub const XG_OFFS_USRL: u8 = 0x14;
+pub const YG_OFFS_USRH: u8 = 0x15;
+pub const YG_OFFS_USRL: u8 = 0x16;
+pub const ZG_OFFS_USRH: u8 = 0x17;
+pub const ZG_OFFS_USRL: u8 = 0x18;
+pub const SMPLRT_DIV: u8 = 0x19;
+pub const CONFIG: u8 = 0x1A;
+pub const GYRO_CONFIG: u8 = 0x1B;
+pub const ACCEL_CONFIG: u8 = 0x1C;
+pub const ACCEL_CONFIG2: u8 = 0x1D;
+pub const LP_MODE_CFG: u8 = 0x1E;
+pub const ACCEL_WOM_X_THR: u8 = 0x20;
+pub const ACCEL_WOM_Y_THR: u8 = 0x21;
+pub const ACCEL_WOM_Z_THR: u8 = 0x22;
+pub const FIFO_EN: u8 = 0x23;
+pub const FSYNC_INT: u8 = 0x36;
+pub const INT_PIN_CFG: u8 = 0x37;
+pub const INT_ENABLE: u8 = 0x38;
+pub const FIFO_WM_INT_STATUS: u8 = 0x39;
+pub const INT_STATUS: u8 = 0x3A;
+pub const ACCEL_XOUT_H: u8 = 0x3B;
+pub const ACCEL_XOUT_L: u8 = 0x3C;
+pub const ACCEL_YOUT_H: u8 = 0x3D;
+pub const ACCEL_YOUT_L: u8 = 0x3E;
+pub const ACCEL_ZOUT_H: u8 = 0x3F;
+pub const ACCEL_ZOUT_L: u8 = 0x40;
+pub const TEMP_OUT_H: u8 = 0x41;
+pub const TEMP_OUT_L: u8 = 0x42;
+pub const GYRO_XOUT_H: u8 = 0x43;
+pub const GYRO_XOUT_L: u8 = 0x44;
+pub const GYRO_YOUT_H: u8 = 0x45;
+pub const GYRO_YOUT_L: u8 = 0x46;
+pub const GYRO_ZOUT_H: u8 = 0x47;
+pub const GYRO_ZOUT_L: u8 = 0x48;
+pub const SELF_TEST_X_GYRO: u8 = 0x50;
+pub const SELF_TEST_Y_GYRO: u8 = 0x51;
+pub const SELF_TEST_Z_GYRO: u8 = 0x52;
+pub const FIFO_WM_TH1: u8 = 0x60;
+pub const FIFO_WM_TH2: u8 = 0x61;
+pub const SIGNAL_PATH_RESET: u8 = 0x68;
+pub const ACCEL_INTEL_CTRL: u8 = 0x69;
+pub const USER_CTRL: u8 = 0x6A;
+pub const PWR_MGMT_1: u8 = 0x6B;
+pub const PWR_MGMT_2: u8 = 0x6C;
+pub const I2C_IF: u8 = 0x70;
+pub const FIFO_COUNTH: u8 = 0x72;
+pub const FIFO_COUNTL: u8 = 0x73;
+pub const FIFO_R_W: u8 = 0x74;
+pub const WHO_AM_I: u8 = 0x75;
+pub const XA_OFFSET_H: u8 = 0x77;
+pub const XA_OFFSET_L: u8 = 0x78;
+pub const YA_OFFSET_H: u8 = 0x7A;
+pub const YA_OFFSET_L: u8 = 0x7B;
+pub const ZA_OFFSET_H: u8 = 0x7D;
+pub const ZA_OFFSET_L: u8 = 0x7E;
+
+/// CONFIG fields.
+pub mod config {
+    pub const FIFO_MODE: u8 = 0b01000000;
+    pub const EXT_SYNC_SET: u8 = 0b00111000;
+    pub const DLPF_CFG: u8 = 0b00000111;
+}
+
+/// GYRO_CONFIG fields.
+pub mod gyro_config {
+    pub const XG_ST: u8 = 0b10000000;
+    pub const YG_ST: u8 = 0b01000000;
+    pub const ZG_ST: u8 = 0b00100000;
+    pub const FS_SEL: u8 = 0b00011000;
+    pub const FCHOICE_B: u8 = 0b00000011;
+}
+
+/// ACCEL_CONFIG fields.
+pub mod accel_config {
+    pub const XA_ST: u8 = 0b10000000;
+    pub const YA_ST: u8 = 0b01000000;
+    pub const ZA_ST: u8 = 0b00100000;
+    pub const ACCEL_FS_SEL: u8 = 0b00011000;
+}
+
+/// ACCEL_CONFIG2 fields.
+pub mod accel_config2 {
+    pub const DEC2_CFG: u8 = 0b00110000;
+    pub const ACCEL_FCHOICE_B: u8 = 0b00001000;
+    pub const A_DLPF_CFG: u8 = 0b00000111;
+}
+
+/// LP_MODE_CFG fields.
+pub mod lp_mode_cfg {
+    pub const GYRO_CYCLE: u8 = 0b10000000;
+    pub const G_AVGCFG: u8 = 0b01110000;
+}
+
+/// XA/YA/ZA_OFFSET_L fields, bit 0 is reserved.
+pub mod accel_offset_l {
+    pub const OFFSET_L: u8 = 0b11111110;
+}
+
+/// FIFO_EN fields.
+pub mod fifo_en {
+    pub const GYRO_FIFO_EN: u8 = 0b00010000;
+    pub const ACCEL_FIFO_EN: u8 = 0b00001000;
+}
+
+/// FSYNC_INT fields.
+pub mod fsync_int {
+    pub const FSYNC_INT: u8 = 0b10000000;
+}
+
+/// INT_PIN_CFG fields.
+pub mod int_pin_cfg {
+    pub const INT_LEVEL: u8 = 0b10000000;
+    pub const INT_OPEN: u8 = 0b01000000;
+    pub const LATCH_INT_EN: u8 = 0b00100000;
+    pub const INT_RD_CLEAR: u8 = 0b00010000;
+    pub const FSYNC_INT_LEVEL: u8 = 0b00001000;
+    pub const FSYNC_INT_MODE_EN: u8 = 0b00000100;
+}
+
+/// INT_ENABLE fields.
+pub mod int_enable {
+    pub const WOM_X_INT_EN: u8 = 0b10000000;
+    pub const WOM_Y_INT_EN: u8 = 0b01000000;
+    pub const WOM_Z_INT_EN: u8 = 0b00100000;
+    pub const WOM_INT_EN: u8 = WOM_X_INT_EN | WOM_Y_INT_EN | WOM_Z_INT_EN;
+    pub const FIFO_OFLOW_EN: u8 = 0b00010000;
+    pub const GDRIVE_INT_EN: u8 = 0b00000100;
+    pub const DATA_RDY_INT_EN: u8 = 0b00000001;
+}
+
+/// FIFO_WM_INT_STATUS fields.
+pub mod fifo_wm_int_status {
+    pub const FIFO_WM_INT: u8 = 0b01000000;
+}
+
+/// INT_STATUS fields.
+pub mod int_status {
+    pub const WOM_X_INT: u8 = 0b10000000;
+    pub const WOM_Y_INT: u8 = 0b01000000;
+    pub const WOM_Z_INT: u8 = 0b00100000;
+    pub const FIFO_OFLOW_INT: u8 = 0b00010000;
+    pub const GDRIVE_INT: u8 = 0b00000100;
+    pub const DATA_RDY_INT: u8 = 0b00000001;
+}
+
+/// FIFO_WM_TH1 fields, the upper bits of the watermark.
+pub mod fifo_wm_th1 {
+    pub const FIFO_WM_TH: u8 = 0b00000011;
+}
+
+/// SIGNAL_PATH_RESET fields.
+pub mod signal_path_reset {
+    pub const ACCEL_RST: u8 = 0b00000010;
+    pub const TEMP_RST: u8 = 0b00000001;
+}
+
+/// ACCEL_INTEL_CTRL fields.
+pub mod accel_intel_ctrl {
+    pub const ACCEL_INTEL_EN: u8 = 0b10000000;
+    pub const ACCEL_INTEL_MODE: u8 = 0b01000000;
+    pub const WOM_TH_MODE: u8 = 0b00000001;
+}
+
+/// USER_CTRL fields.
+pub mod user_ctrl {
+    pub const FIFO_EN: u8 = 0b01000000;
+    pub const I2C_IF_DIS: u8 = 0b00010000;
+    pub const FIFO_RST: u8 = 0b00000100;
+    pub const SIG_COND_RST: u8 = 0b00000001;
+}
+
+/// PWR_MGMT_1 fields.
+pub mod pwr_mgmt_1 {
+    pub const DEVICE_RESET: u8 = 0b10000000;
+    pub const SLEEP: u8 = 0b01000000;
+    pub const CYCLE: u8 = 0b00100000;
+    pub const GYRO_STANDBY: u8 = 0b00010000;
+    pub const TEMP_DIS: u8 = 0b00001000;
+    pub const CLKSEL: u8 = 0b00000111;
+}
+
+/// PWR_MGMT_2 fields.
+pub mod pwr_mgmt_2 {
+    pub const STBY_XA: u8 = 0b00100000;
+    pub const STBY_YA: u8 = 0b00010000;
+    pub const STBY_ZA: u8 = 0b00001000;
+    pub const STBY_XG: u8 = 0b00000100;
+    pub const STBY_YG: u8 = 0b00000010;
+    pub const STBY_ZG: u8 = 0b00000001;
+    pub const STBY_A: u8 = STBY_XA | STBY_YA | STBY_ZA;
+    pub const STBY_G: u8 = STBY_XG | STBY_YG | STBY_ZG;
+}
@@ -0,0 +1,89 @@
+//! Reset implementation.
+
+use crate::registers::pwr_mgmt_1::{CYCLE, DEVICE_RESET, GYRO_STANDBY, SLEEP};
+use crate::registers::signal_path_reset;
+use crate::{registers, Error, Interface, Mpu6886};
+
+use embedded_hal::delay::DelayNs;
+
+/// Digital signal path to reset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SignalPath {
+    /// Accelerometer digital signal path (SIGNAL_PATH_RESET ACCEL_RST).
+    Accelerometer,
+    /// Temperature sensor digital signal path (SIGNAL_PATH_RESET TEMP_RST).
+    Temperature,
+    /// All signal paths, also clearing the sensor registers (USER_CTRL
+    /// SIG_COND_RST).
+    All,
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Resets the chip, waits until it's back and checks the chip version.
+    ///
+    /// Polls DEVICE_RESET every millisecond for up to 100 ms, bus errors
+    /// while the chip restarts count as not ready.
+    pub fn reset_and_wait(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<DI::Error>> {
+        self.reset()?;
+        for _ in 0..100 {
+            delay.delay_ms(1);
+            // bypasses the register cache, DEVICE_RESET clears itself
+            let mut buf: [u8; 1] = [0; 1];
+            match self.read_buf(registers::PWR_MGMT_1, &mut buf) {
+                Ok(_) if buf[0] & DEVICE_RESET == 0 => return self.init(),
+                _ => {}
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    /// Recovers from a hung chip: saves the configuration and power state,
+    /// resets the chip, checks the chip version and restores both.
+    ///
+    /// The configuration is read with [`Mpu6886::read_config`], so changes
+    /// made by any setter are kept. If the chip doesn't answer before the
+    /// reset, the configuration last given to [`Mpu6886::apply_config`] is
+    /// applied instead and the chip stays asleep. Hardware offsets and
+    /// settings outside of [`Mpu6886Config`](crate::Mpu6886Config) are lost.
+    pub fn recover(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<DI::Error>> {
+        let saved = self.read_config().and_then(|config| {
+            let pwr_mgmt_1 = self.read_u8(registers::PWR_MGMT_1)?;
+            let pwr_mgmt_2 = self.read_u8(registers::PWR_MGMT_2)?;
+            Ok((config, pwr_mgmt_1, pwr_mgmt_2))
+        });
+        self.reset_and_wait(delay)?;
+        match saved {
+            Ok((config, pwr_mgmt_1, pwr_mgmt_2)) => {
+                self.apply_config(&config)?;
+                self.write_u8(registers::PWR_MGMT_2, pwr_mgmt_2)?;
+                // the rest was set by the config
+                let power_bits = SLEEP | CYCLE | GYRO_STANDBY;
+                let original_value = self.read_u8(registers::PWR_MGMT_1)?;
+                let new_value = original_value & !power_bits | pwr_mgmt_1 & power_bits;
+                self.write_u8(registers::PWR_MGMT_1, new_value)
+            }
+            Err(_) => match self.last_config {
+                Some(config) => self.apply_config(&config),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Resets a digital signal path, the bits clear themselves.
+    pub fn reset_signal_paths(&mut self, path: SignalPath) -> Result<(), Error<DI::Error>> {
+        match path {
+            SignalPath::Accelerometer => {
+                self.write_u8(registers::SIGNAL_PATH_RESET, signal_path_reset::ACCEL_RST)
+            }
+            SignalPath::Temperature => {
+                self.write_u8(registers::SIGNAL_PATH_RESET, signal_path_reset::TEMP_RST)
+            }
+            SignalPath::All => {
+                let original_value = self.read_u8(registers::USER_CTRL)?;
+                let new_value = original_value | registers::user_ctrl::SIG_COND_RST;
+                self.write_u8(registers::USER_CTRL, new_value)
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Self-test implementation.
+
+use crate::model::Feature;
+use crate::registers::accel_config::{XA_ST, YA_ST, ZA_ST};
+use crate::registers::gyro_config::{XG_ST, YG_ST, ZG_ST};
+use crate::{
+    registers, AccelScaleRange, Error, GyroScaleRange, Interface, Mpu6886, ACCEL_SENSITIVITY_2G,
+    GYRO_SENSITIVITY_250DPS,
+};
+
+use embedded_hal::delay::DelayNs;
+
+/// Number of samples averaged for each self-test measurement.
+const SELF_TEST_SAMPLES: u16 = 200;
+
+/// Averaged raw readings, (X, Y, Z).
+pub(crate) type RawAverage = (i32, i32, i32);
+
+/// Per-axis self-test outcome, (X, Y, Z), `true` means passed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestResult {
+    pub accel: (bool, bool, bool),
+    pub gyro: (bool, bool, bool),
+}
+
+impl SelfTestResult {
+    /// Returns whether all axes passed.
+    pub fn passed(&self) -> bool {
+        let (ax, ay, az) = self.accel;
+        let (gx, gy, gz) = self.gyro;
+        ax && ay && az && gx && gy && gz
+    }
+}
+
+/// Returns the expected self-test response from a factory trim code, in LSB
+/// at the lowest full scale range. Returns 0 for a missing trim.
+fn factory_response(code: u8) -> f32 {
+    if code == 0 {
+        return 0.0;
+    }
+    // 2620 * 1.01 ^ (code - 1), powf is not available in core
+    let mut value = 2620.0;
+    for _ in 1..code {
+        value *= 1.01;
+    }
+    value
+}
+
+/// Checks the gyroscope response, in LSB at ±250 dps.
+fn gyro_axis_passed(response: i32, code: u8) -> bool {
+    let expected = factory_response(code);
+    if expected == 0.0 {
+        // at least 60 dps
+        response.abs() >= 60 * GYRO_SENSITIVITY_250DPS as i32
+    } else {
+        response as f32 / expected >= 0.5
+    }
+}
+
+/// Checks the accelerometer response, in LSB at ±2 g.
+fn accel_axis_passed(response: i32, code: u8) -> bool {
+    let expected = factory_response(code);
+    if expected == 0.0 {
+        // between 225 mg and 675 mg
+        let lsb_per_g = ACCEL_SENSITIVITY_2G as i32;
+        (225 * lsb_per_g / 1000..=675 * lsb_per_g / 1000).contains(&response.abs())
+    } else {
+        (0.5..=1.5).contains(&(response as f32 / expected))
+    }
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Runs the datasheet self-test sequence.
+    ///
+    /// The chip must be awake with both sensors on, and held still. The
+    /// sample rate and sensor configuration are restored afterwards, also
+    /// when the test fails with an error. Takes about half a second.
+    pub fn self_test(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<SelfTestResult, Error<DI::Error>> {
+        self.model.require(Feature::SelfTest)?;
+        // SMPLRT_DIV, CONFIG, GYRO_CONFIG, ACCEL_CONFIG, ACCEL_CONFIG2
+        let mut saved: [u8; 5] = [0; 5];
+        self.read_buf(registers::SMPLRT_DIV, &mut saved)?;
+
+        let measured = self.measure_self_test_response(delay);
+        let restored = self.write_buf(registers::SMPLRT_DIV, &saved);
+        if restored.is_ok() {
+            self.acc_range = AccelScaleRange::from_reg(saved[3]);
+            self.gyro_range = GyroScaleRange::from_reg(saved[2]);
+        } else {
+            // the self-test ranges are still set
+            self.acc_range = AccelScaleRange::Range2g;
+            self.gyro_range = GyroScaleRange::Range250Dps;
+        }
+        let (accel_response, gyro_response) = measured?;
+        restored?;
+        delay.delay_ms(20);
+
+        let mut accel_codes: [u8; 3] = [0; 3];
+        self.read_buf(registers::SELF_TEST_X_ACCEL, &mut accel_codes)?;
+        let mut gyro_codes: [u8; 3] = [0; 3];
+        self.read_buf(self.model.self_test_gyro_reg(), &mut gyro_codes)?;
+
+        Ok(SelfTestResult {
+            accel: (
+                accel_axis_passed(accel_response.0, accel_codes[0]),
+                accel_axis_passed(accel_response.1, accel_codes[1]),
+                accel_axis_passed(accel_response.2, accel_codes[2]),
+            ),
+            gyro: (
+                gyro_axis_passed(gyro_response.0, gyro_codes[0]),
+                gyro_axis_passed(gyro_response.1, gyro_codes[1]),
+                gyro_axis_passed(gyro_response.2, gyro_codes[2]),
+            ),
+        })
+    }
+
+    /// Measures the self-test response, (accel, gyro), leaving the self-test
+    /// configuration set.
+    fn measure_self_test_response(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(RawAverage, RawAverage), Error<DI::Error>> {
+        // 1 kHz, gyro DLPF 92 Hz, ±250 dps, ±2 g, accel DLPF 99 Hz
+        self.write_buf(registers::SMPLRT_DIV, &[0, 2, 0, 0, 2])?;
+        delay.delay_ms(20);
+        let (accel_normal, gyro_normal) = self.average_raw(delay, SELF_TEST_SAMPLES)?;
+
+        // enable self-test on all axes
+        self.write_u8(registers::GYRO_CONFIG, XG_ST | YG_ST | ZG_ST)?;
+        self.write_u8(registers::ACCEL_CONFIG, XA_ST | YA_ST | ZA_ST)?;
+        delay.delay_ms(20);
+        let (accel_test, gyro_test) = self.average_raw(delay, SELF_TEST_SAMPLES)?;
+
+        Ok((
+            (
+                accel_test.0 - accel_normal.0,
+                accel_test.1 - accel_normal.1,
+                accel_test.2 - accel_normal.2,
+            ),
+            (
+                gyro_test.0 - gyro_normal.0,
+                gyro_test.1 - gyro_normal.1,
+                gyro_test.2 - gyro_normal.2,
+            ),
+        ))
+    }
+
+    /// Averages raw accelerometer and gyroscope readings, 1 ms apart.
+    pub(crate) fn average_raw(
+        &mut self,
+        delay: &mut impl DelayNs,
+        samples: u16,
+    ) -> Result<(RawAverage, RawAverage), Error<DI::Error>> {
+        let samples = samples.max(1);
+        let mut accel_sum = (0i32, 0i32, 0i32);
+        let mut gyro_sum = (0i32, 0i32, 0i32);
+        for _ in 0..samples {
+            let accel = self.acceleration_raw()?;
+            let gyro = self.gyro_raw()?;
+            accel_sum.0 += accel.0 as i32;
+            accel_sum.1 += accel.1 as i32;
+            accel_sum.2 += accel.2 as i32;
+            gyro_sum.0 += gyro.0 as i32;
+            gyro_sum.1 += gyro.1 as i32;
+            gyro_sum.2 += gyro.2 as i32;
+            delay.delay_ms(1);
+        }
+        let n = samples as i32;
+        Ok((
+            (accel_sum.0 / n, accel_sum.1 / n, accel_sum.2 / n),
+            (gyro_sum.0 / n, gyro_sum.1 / n, gyro_sum.2 / n),
+        ))
+    }
+}
@@ -0,0 +1,164 @@
+//! Split driver implementation.
+//!
+//! [`Mpu6886::split`] hands the data reads to a [`Mpu6886Reader`], e.g. for
+//! an interrupt handler, while the [`Mpu6886Control`] half keeps the
+//! configuration. Both halves need their own handle to the bus, such as two
+//! devices from a shared bus wrapper.
+
+use crate::measurement::Conversion;
+use crate::mounting::Mounting;
+use crate::{
+    check_ready, read_chunked, AccelScaleRange, CalibrationData, Error, GyroScaleRange, Interface,
+    Measurement, Model, Mpu6886, RawAccelGyroFrame, RawMeasurement,
+};
+
+/// Configuration half of a split driver.
+#[derive(Debug)]
+pub struct Mpu6886Control<DI> {
+    driver: Mpu6886<DI>,
+}
+
+/// Data half of a split driver, only reads samples.
+///
+/// It converts readings with the ranges, calibration, mounting correction and
+/// dead-band copied at split time, use [`Mpu6886Control::update_reader`] after
+/// changing them. The limit set with [`Mpu6886::set_max_read_len`] is copied
+/// as well, and so is the power state: like the driver, reads fail with
+/// [`Error::NotInitialized`] or [`Error::Asleep`] until the reader is updated
+/// after [`Mpu6886::init`] and waking the chip.
+#[derive(Debug)]
+pub struct Mpu6886Reader<DI> {
+    iface: DI,
+    acc_range: AccelScaleRange,
+    gyro_range: GyroScaleRange,
+    calibration: CalibrationData,
+    model: Model,
+    mounting: Mounting,
+    accel_dead_band: u16,
+    max_read_len: Option<usize>,
+    initialized: bool,
+    asleep: bool,
+}
+
+impl<DI: Interface> Mpu6886<DI> {
+    /// Splits the driver into a configuration and a data half, the data half
+    /// talking through `reader_iface`.
+    pub fn split<R: Interface>(self, reader_iface: R) -> (Mpu6886Control<DI>, Mpu6886Reader<R>) {
+        let reader = Mpu6886Reader {
+            iface: reader_iface,
+            acc_range: self.acc_range,
+            gyro_range: self.gyro_range,
+            calibration: self.calibration,
+            model: self.model,
+            mounting: self.mounting,
+            accel_dead_band: self.accel_dead_band,
+            max_read_len: self.max_read_len,
+            initialized: self.initialized,
+            asleep: self.asleep,
+        };
+        (Mpu6886Control { driver: self }, reader)
+    }
+}
+
+impl<DI: Interface> Mpu6886Control<DI> {
+    /// Gives access to the underlying driver for configuration.
+    pub fn driver(&mut self) -> &mut Mpu6886<DI> {
+        &mut self.driver
+    }
+
+    /// Copies the current ranges, calibration, mounting correction, dead-band,
+    /// read length limit and power state to `reader`.
+    pub fn update_reader<R>(&self, reader: &mut Mpu6886Reader<R>) {
+        reader.acc_range = self.driver.acc_range;
+        reader.gyro_range = self.driver.gyro_range;
+        reader.calibration = self.driver.calibration;
+        reader.model = self.driver.model;
+        reader.mounting = self.driver.mounting;
+        reader.accel_dead_band = self.driver.accel_dead_band;
+        reader.max_read_len = self.driver.max_read_len;
+        reader.initialized = self.driver.initialized;
+        reader.asleep = self.driver.asleep;
+    }
+
+    /// Joins both halves, giving back the driver and the reader's interface.
+    pub fn join<R>(self, reader: Mpu6886Reader<R>) -> (Mpu6886<DI>, R) {
+        (self.driver, reader.iface)
+    }
+}
+
+impl<DI: Interface> Mpu6886Reader<DI> {
+    /// See [`Mpu6886::read_all`].
+    pub fn read_all(&mut self) -> Result<Measurement, Error<DI::Error>> {
+        let raw = self.read_all_raw()?;
+        let conversion = Conversion::new(
+            self.acc_range,
+            self.gyro_range,
+            &self.calibration,
+            &self.mounting,
+            self.model,
+            self.accel_dead_band,
+        );
+        Ok(conversion.measurement(raw))
+    }
+
+    /// See [`Mpu6886::read_all_raw`].
+    pub fn read_all_raw(&mut self) -> Result<RawMeasurement, Error<DI::Error>> {
+        let mut frame = RawAccelGyroFrame::default();
+        self.read_frame(&mut frame)?;
+        Ok(frame.into())
+    }
+
+    /// See [`Mpu6886::read_frame`].
+    pub fn read_frame(&mut self, frame: &mut RawAccelGyroFrame) -> Result<(), Error<DI::Error>> {
+        check_ready(self.initialized, self.asleep)?;
+        read_chunked(
+            &mut self.iface,
+            RawAccelGyroFrame::BASE_REGISTER,
+            &mut frame.0,
+            self.max_read_len,
+        )?;
+        #[cfg(feature = "trace")]
+        crate::trace_read(RawAccelGyroFrame::BASE_REGISTER, &frame.0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::{registers, I2cInterface, MPU6886_DEFAULT_ADDR};
+
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+    use std::vec;
+
+    #[test]
+    fn reader_checks_driver_state() {
+        let address = MPU6886_DEFAULT_ADDR;
+        let expectations = [
+            Transaction::write_read(address, vec![registers::ACCEL_CONFIG], vec![0]),
+            Transaction::write_read(address, vec![registers::GYRO_CONFIG], vec![0]),
+            Transaction::write_read(address, vec![registers::PWR_MGMT_1], vec![0b00000001]),
+        ];
+        let reader_expectations = [Transaction::write_read(
+            address,
+            vec![registers::ACCEL_XOUT_H],
+            vec![0; 14],
+        )];
+        let sensor = Mpu6886::new(Mock::new(&expectations));
+        let (mut control, mut reader) =
+            sensor.split(I2cInterface::new(Mock::new(&reader_expectations), address));
+        let mut frame = RawAccelGyroFrame::default();
+        assert!(matches!(
+            reader.read_frame(&mut frame),
+            Err(Error::NotInitialized)
+        ));
+        control.driver().init_unchecked().unwrap();
+        control.update_reader(&mut reader);
+        reader.read_frame(&mut frame).unwrap();
+        let (driver, reader_iface) = control.join(reader);
+        driver.destroy().done();
+        reader_iface.release().done();
+    }
+}
@@ -0,0 +1,99 @@
+//! Running statistics of measurements.
+
+use crate::Measurement;
+
+/// Running mean, variance, min and max of one channel, using Welford's
+/// algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisStats {
+    count: u32,
+    mean: f32,
+    m2: f32,
+    min: f32,
+    max: f32,
+}
+
+impl AxisStats {
+    pub fn add(&mut self, value: f32) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f32;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.mean
+    }
+
+    /// Returns the sample variance, 0 with less than two values.
+    pub fn variance(&self) -> f32 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f32
+        }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+}
+
+/// Per-axis statistics of measurements, e.g. to characterize the noise of a
+/// stationary device:
+///
+/// ```rust,ignore
+/// let mut stats = SampleStats::default();
+/// for _ in 0..1000 {
+///     stats.add(&sensor.read_all()?);
+/// }
+/// let gyro_x_variance = stats.gyro.0.variance();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SampleStats {
+    /// Acceleration, (X, Y, Z), in m/s².
+    pub accel: (AxisStats, AxisStats, AxisStats),
+    /// Angular rate, (X, Y, Z), in rad/s.
+    pub gyro: (AxisStats, AxisStats, AxisStats),
+    /// Temperature in °C.
+    pub temp: AxisStats,
+}
+
+impl SampleStats {
+    pub fn add(&mut self, measurement: &Measurement) {
+        self.accel.0.add(measurement.accel.0);
+        self.accel.1.add(measurement.accel.1);
+        self.accel.2.add(measurement.accel.2);
+        self.gyro.0.add(measurement.gyro.0);
+        self.gyro.1.add(measurement.gyro.1);
+        self.gyro.2.add(measurement.gyro.2);
+        self.temp.add(measurement.temp);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.temp.count()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,76 @@
+//! Sample writer for data capture over `embedded_io::Write`.
+
+use embedded_io::{Write, WriteFmtError};
+
+use crate::Measurement;
+
+/// Framing used by [`SampleWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SampleFormat {
+    /// [`SampleWriter::FRAME_START`] followed by accel X, Y, Z, gyro X, Y, Z
+    /// and temperature as little-endian `f32`, 29 bytes per sample.
+    #[default]
+    Binary,
+    /// One line per sample, `ax,ay,az,gx,gy,gz,temp` in m/s², rad/s and °C.
+    Csv,
+}
+
+/// Writes measurements to a serial port, USB or any other
+/// `embedded_io::Write`.
+#[derive(Debug)]
+pub struct SampleWriter<W> {
+    writer: W,
+    format: SampleFormat,
+}
+
+impl<W: Write> SampleWriter<W> {
+    /// First byte of every binary frame.
+    pub const FRAME_START: u8 = 0xA5;
+
+    pub fn new(writer: W, format: SampleFormat) -> Self {
+        Self { writer, format }
+    }
+
+    pub fn release(self) -> W {
+        self.writer
+    }
+
+    /// Writes the CSV column names, does nothing in binary format.
+    pub fn write_header(&mut self) -> Result<(), WriteFmtError<W::Error>> {
+        if self.format == SampleFormat::Csv {
+            self.writer
+                .write_all(b"ax,ay,az,gx,gy,gz,temp\n")
+                .map_err(WriteFmtError::Other)?;
+        }
+        Ok(())
+    }
+
+    pub fn write(&mut self, measurement: &Measurement) -> Result<(), WriteFmtError<W::Error>> {
+        let (ax, ay, az) = measurement.accel;
+        let (gx, gy, gz) = measurement.gyro;
+        match self.format {
+            SampleFormat::Binary => {
+                let mut frame: [u8; 29] = [0; 29];
+                frame[0] = Self::FRAME_START;
+                for (bytes, value) in
+                    frame[1..]
+                        .chunks_exact_mut(4)
+                        .zip([ax, ay, az, gx, gy, gz, measurement.temp])
+                {
+                    bytes.copy_from_slice(&value.to_le_bytes());
+                }
+                self.writer.write_all(&frame).map_err(WriteFmtError::Other)
+            }
+            SampleFormat::Csv => writeln!(
+                self.writer,
+                "{ax:.4},{ay:.4},{az:.4},{gx:.4},{gy:.4},{gz:.4},{:.2}",
+                measurement.temp
+            ),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), W::Error> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,175 @@
+//! Typestate wrapper around [`Mpu6886`].
+//!
+//! [`Imu`] tracks the chip state in its type, so measurements can only be
+//! taken once the chip has been initialized and woken up:
+//!
+//! ```rust,ignore
+//! use mpu6886::typestate::Imu;
+//!
+//! let imu = Imu::new(i2c).init().unwrap();
+//! let mut imu = imu.wake().unwrap();
+//! let acc = imu.acceleration().unwrap();
+//! let imu = imu.sleep().unwrap();
+//! ```
+
+// Failed transitions hand the driver back, boxing it would need an allocator.
+#![allow(clippy::result_large_err)]
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::{Error, I2c, I2cInterface, Interface, Measurement, Mpu6886, PowerMode};
+
+/// Chip not checked yet.
+#[derive(Debug)]
+pub struct Uninitialized;
+/// Chip checked and in sleep mode.
+#[derive(Debug)]
+pub struct Asleep;
+/// Accelerometer and gyroscope running in low noise mode.
+#[derive(Debug)]
+pub struct Active;
+/// Accelerometer running in low power mode, gyroscope off.
+#[derive(Debug)]
+pub struct LowPower;
+
+/// Error of a failed state transition, gives the device back unchanged.
+pub struct TransitionError<T, E> {
+    pub device: T,
+    pub error: Error<E>,
+}
+
+impl<T, E: fmt::Debug> fmt::Debug for TransitionError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+type Transition<DI, From, To> =
+    Result<Imu<DI, To>, TransitionError<Imu<DI, From>, <DI as Interface>::Error>>;
+
+/// [`Mpu6886`] with its state tracked in the type.
+#[derive(Debug)]
+pub struct Imu<DI, S> {
+    driver: Mpu6886<DI>,
+    _state: PhantomData<S>,
+}
+
+impl<DI: Interface, S> Imu<DI, S> {
+    fn transition<T>(self, result: Result<(), Error<DI::Error>>) -> Transition<DI, S, T> {
+        match result {
+            Ok(()) => Ok(Imu {
+                driver: self.driver,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(TransitionError {
+                device: self,
+                error,
+            }),
+        }
+    }
+
+    /// Gives access to the underlying driver for configuration.
+    ///
+    /// Changing the power state through it bypasses the typestate checks.
+    pub fn driver(&mut self) -> &mut Mpu6886<DI> {
+        &mut self.driver
+    }
+
+    pub fn release(self) -> Mpu6886<DI> {
+        self.driver
+    }
+}
+
+impl<I2C: I2c> Imu<I2cInterface<I2C>, Uninitialized> {
+    #[must_use]
+    pub fn new(i2c: I2C) -> Self {
+        Self::from_driver(Mpu6886::new(i2c))
+    }
+}
+
+impl<DI: Interface> Imu<DI, Uninitialized> {
+    #[must_use]
+    pub fn from_driver(driver: Mpu6886<DI>) -> Self {
+        Self {
+            driver,
+            _state: PhantomData,
+        }
+    }
+
+    /// Checks the chip version, loads its state and puts it to sleep.
+    pub fn init(mut self) -> Transition<DI, Uninitialized, Asleep> {
+        let result = self
+            .driver
+            .init()
+            .and_then(|_| self.driver.set_power_mode(PowerMode::Sleep));
+        self.transition(result)
+    }
+}
+
+impl<DI: Interface> Imu<DI, Asleep> {
+    /// Starts both sensors in low noise mode.
+    pub fn wake(mut self) -> Transition<DI, Asleep, Active> {
+        let result = self.driver.set_power_mode(PowerMode::SixAxisLowNoise);
+        self.transition(result)
+    }
+
+    /// Starts the accelerometer in low power mode.
+    pub fn low_power(mut self) -> Transition<DI, Asleep, LowPower> {
+        let result = self.driver.set_power_mode(PowerMode::AccelLowPower);
+        self.transition(result)
+    }
+}
+
+impl<DI: Interface> Imu<DI, Active> {
+    pub fn sleep(mut self) -> Transition<DI, Active, Asleep> {
+        let result = self.driver.set_power_mode(PowerMode::Sleep);
+        self.transition(result)
+    }
+
+    /// Turns the gyroscope off and runs the accelerometer in low power mode.
+    pub fn low_power(mut self) -> Transition<DI, Active, LowPower> {
+        let result = self.driver.set_power_mode(PowerMode::AccelLowPower);
+        self.transition(result)
+    }
+
+    /// See [`Mpu6886::acceleration`].
+    pub fn acceleration(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        self.driver.acceleration()
+    }
+
+    /// See [`Mpu6886::gyro`].
+    pub fn gyro(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        self.driver.gyro()
+    }
+
+    /// See [`Mpu6886::temperature`].
+    pub fn temperature(&mut self) -> Result<f32, Error<DI::Error>> {
+        self.driver.temperature()
+    }
+
+    /// See [`Mpu6886::read_all`].
+    pub fn read_all(&mut self) -> Result<Measurement, Error<DI::Error>> {
+        self.driver.read_all()
+    }
+}
+
+impl<DI: Interface> Imu<DI, LowPower> {
+    pub fn sleep(mut self) -> Transition<DI, LowPower, Asleep> {
+        let result = self.driver.set_power_mode(PowerMode::Sleep);
+        self.transition(result)
+    }
+
+    /// Starts both sensors in low noise mode.
+    pub fn wake(mut self) -> Transition<DI, LowPower, Active> {
+        let result = self.driver.set_power_mode(PowerMode::SixAxisLowNoise);
+        self.transition(result)
+    }
+
+    /// See [`Mpu6886::acceleration`].
+    pub fn acceleration(&mut self) -> Result<(f32, f32, f32), Error<DI::Error>> {
+        self.driver.acceleration()
+    }
+}
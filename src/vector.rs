@@ -0,0 +1,65 @@
+//! Vector type conversions.
+
+use crate::{Error, Interface, Measurement, Mpu6886};
+
+#[cfg(feature = "mint")]
+fn mint_vector((x, y, z): (f32, f32, f32)) -> mint::Vector3<f32> {
+    mint::Vector3 { x, y, z }
+}
+
+#[cfg(feature = "nalgebra")]
+fn nalgebra_vector((x, y, z): (f32, f32, f32)) -> nalgebra::Vector3<f32> {
+    nalgebra::Vector3::new(x, y, z)
+}
+
+#[cfg(feature = "mint")]
+impl Measurement {
+    /// Returns the acceleration in m/s².
+    pub fn accel_mint(&self) -> mint::Vector3<f32> {
+        mint_vector(self.accel)
+    }
+
+    /// Returns the angular rate in rad/s.
+    pub fn gyro_mint(&self) -> mint::Vector3<f32> {
+        mint_vector(self.gyro)
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl Measurement {
+    /// Returns the acceleration in m/s².
+    pub fn accel_nalgebra(&self) -> nalgebra::Vector3<f32> {
+        nalgebra_vector(self.accel)
+    }
+
+    /// Returns the angular rate in rad/s.
+    pub fn gyro_nalgebra(&self) -> nalgebra::Vector3<f32> {
+        nalgebra_vector(self.gyro)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl<DI: Interface> Mpu6886<DI> {
+    /// Same as [`Mpu6886::acceleration`], as a mint vector.
+    pub fn acceleration_mint(&mut self) -> Result<mint::Vector3<f32>, Error<DI::Error>> {
+        Ok(mint_vector(self.acceleration()?))
+    }
+
+    /// Same as [`Mpu6886::gyro`], as a mint vector.
+    pub fn gyro_mint(&mut self) -> Result<mint::Vector3<f32>, Error<DI::Error>> {
+        Ok(mint_vector(self.gyro()?))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl<DI: Interface> Mpu6886<DI> {
+    /// Same as [`Mpu6886::acceleration`], as a nalgebra vector.
+    pub fn acceleration_nalgebra(&mut self) -> Result<nalgebra::Vector3<f32>, Error<DI::Error>> {
+        Ok(nalgebra_vector(self.acceleration()?))
+    }
+
+    /// Same as [`Mpu6886::gyro`], as a nalgebra vector.
+    pub fn gyro_nalgebra(&mut self) -> Result<nalgebra::Vector3<f32>, Error<DI::Error>> {
+        Ok(nalgebra_vector(self.gyro()?))
+    }
+}
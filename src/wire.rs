@@ -0,0 +1,42 @@
+//! Postcard wire format of samples.
+//!
+//! Samples are encoded with postcard, fields in declaration order. The field
+//! order and types are part of the format, so a host decoding with the same
+//! crate version reads them back unchanged.
+
+use crate::{FifoSample, Measurement, RawMeasurement};
+
+impl Measurement {
+    /// Encodes the measurement into `buf`, returns the used part.
+    ///
+    /// An encoded measurement takes at most 28 bytes.
+    pub fn to_postcard<'a>(&self, buf: &'a mut [u8]) -> postcard::Result<&'a mut [u8]> {
+        postcard::to_slice(self, buf)
+    }
+
+    pub fn from_postcard(bytes: &[u8]) -> postcard::Result<Self> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+impl RawMeasurement {
+    /// Encodes the measurement into `buf`, returns the used part.
+    pub fn to_postcard<'a>(&self, buf: &'a mut [u8]) -> postcard::Result<&'a mut [u8]> {
+        postcard::to_slice(self, buf)
+    }
+
+    pub fn from_postcard(bytes: &[u8]) -> postcard::Result<Self> {
+        postcard::from_bytes(bytes)
+    }
+}
+
+impl FifoSample {
+    /// Encodes the sample into `buf`, returns the used part.
+    pub fn to_postcard<'a>(&self, buf: &'a mut [u8]) -> postcard::Result<&'a mut [u8]> {
+        postcard::to_slice(self, buf)
+    }
+
+    pub fn from_postcard(bytes: &[u8]) -> postcard::Result<Self> {
+        postcard::from_bytes(bytes)
+    }
+}